@@ -1,4 +1,5 @@
 use tinkoff_invest_sdk::instruments::InstrumentRequest;
+use tinkoff_invest_sdk::types::Figi;
 use tinkoff_invest_sdk::TinkoffInvestClient;
 
 #[tokio::main]
@@ -8,7 +9,7 @@ async fn main() {
     let client = TinkoffInvestClient::connect(&token).await.unwrap();
     let mut instruments_client = client.instruments();    
     
-    let bond_figi = "BBG00R05JT04".to_string(); // Черкизово выпуск 2
+    let bond_figi: Figi = "BBG00R05JT04".parse().unwrap(); // Черкизово выпуск 2
     let request = InstrumentRequest::Figi(bond_figi);
     
     // Игнорируем ошибку через unwrap. Не рекомендуется делать так в production
@@ -16,5 +17,5 @@ async fn main() {
     
     let name = bond.name();
     let price = bond.placement_price().unwrap();
-    println!("{}\t{} {}", &name, &price.amount, &price.currency);
+    println!("{}\t{} {}", &name, price.value(), price.currency());
 }
@@ -0,0 +1,30 @@
+use tinkoff_invest_sdk::decimal::rust_decimal::Decimal;
+use tinkoff_invest_sdk::instruments::InstrumentRequest;
+use tinkoff_invest_sdk::types::Figi;
+use tinkoff_invest_sdk::TinkoffInvestClient;
+
+#[tokio::main]
+async fn main() {
+    let token = std::env::var("TOKEN").unwrap();
+
+    let client = TinkoffInvestClient::connect(&token).await.unwrap();
+    let mut instruments_client = client.instruments();
+
+    let future_figi: Figi = "FUTRTS122200".parse().unwrap(); // Фьючерс на индекс РТС
+    let request = InstrumentRequest::Figi(future_figi.clone());
+
+    // Игнорируем ошибку через unwrap. Не рекомендуется делать так в production
+    let future = instruments_client.future_by(request).await.unwrap().unwrap();
+    let margin = instruments_client
+        .get_futures_margin(&future_figi)
+        .await
+        .unwrap();
+
+    let price = Decimal::from(100_000);
+    println!(
+        "{}: {} пунктов = {} руб. за контракт",
+        future.name(),
+        price,
+        future.points_to_money(price, &margin).unwrap()
+    );
+}
@@ -0,0 +1,28 @@
+use tinkoff_invest_sdk::chrono::{Duration, Utc};
+use tinkoff_invest_sdk::types::{CandleInterval, Figi};
+use tinkoff_invest_sdk::TinkoffInvestClient;
+
+#[tokio::main]
+async fn main() {
+    let token = std::env::var("TOKEN").unwrap();
+
+    let client = TinkoffInvestClient::connect(&token).await.unwrap();
+    let mut market_data_client = client.market_data();
+
+    let figi: Figi = "BBG004730N88".parse().unwrap(); // Сбербанк
+    let to = Utc::now();
+    let from = to - Duration::hours(6);
+
+    // Игнорируем ошибку через unwrap. Не рекомендуется делать так в production
+    let candles = market_data_client
+        .get_candles(figi, CandleInterval::FifteenMinutes, from..to)
+        .await
+        .unwrap();
+
+    for candle in candles {
+        println!(
+            "{}\tO {}\tH {}\tL {}\tC {}\tV {}",
+            candle.time, candle.open, candle.high, candle.low, candle.close, candle.volume
+        );
+    }
+}
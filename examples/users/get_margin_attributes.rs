@@ -1,4 +1,4 @@
-use tinkoff_invest_sdk::{TinkoffInvestClient, types::MoneyValue};
+use tinkoff_invest_sdk::TinkoffInvestClient;
 
 #[tokio::main]
 async fn main() {
@@ -16,14 +16,14 @@ async fn main() {
         .unwrap();
 
     println!("Название счёта: {}", first_account.name());
-    if let Some(MoneyValue { currency, amount }) = margin_attributes.liquid_portfolio() {
-        println!("Ликвидный портфель: {amount} {currency}");
+    if let Some(money) = margin_attributes.liquid_portfolio() {
+        println!("Ликвидный портфель: {} {}", money.value(), money.currency());
     }
-    if let Some(MoneyValue { currency, amount }) = margin_attributes.minimal_margin() {
-        println!("Минимальная маржа: {amount} {currency}");
+    if let Some(money) = margin_attributes.minimal_margin() {
+        println!("Минимальная маржа: {} {}", money.value(), money.currency());
     }
-    if let Some(MoneyValue { currency, amount }) = margin_attributes.starting_margin() {
-        println!("Начальная маржа: {amount} {currency}");
+    if let Some(money) = margin_attributes.starting_margin() {
+        println!("Начальная маржа: {} {}", money.value(), money.currency());
     }
     if let Some(funds_sufficiency_level) = margin_attributes.funds_sufficiency_level() {
         println!("Обеспечение: {}", funds_sufficiency_level);
@@ -22,31 +22,70 @@ fn ensure_path(api_path: &Path) -> io::Result<()> {
 }
 
 fn generate_mod_rs(api_path: &Path) -> io::Result<()> {
-    std::fs::write(
-        api_path.join("mod.rs"),
+    let contents = if json_enabled() {
+        r##"pub mod tinkoff_invest_v1 {
+    include!("tinkoff.public.invest.api.contract.v1.rs");
+    include!("tinkoff.public.invest.api.contract.v1.serde.rs");
+}
+"##
+    } else {
         r##"#[path = "tinkoff.public.invest.api.contract.v1.rs"]
 pub mod tinkoff_invest_v1;
-"##,
-    )
+"##
+    };
+    std::fs::write(api_path.join("mod.rs"), contents)
+}
+
+/// Фича `json`: к сообщениям генерируется каноническое JSON-представление proto3 (pbjson).
+fn json_enabled() -> bool {
+    cfg!(feature = "json")
 }
 
-fn generate_code_from_contracts(api_path: &Path, proto_path: &Path) -> io::Result<()> {
+fn generate_code_from_contracts(
+    api_path: &Path,
+    proto_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
     let contracts_path = proto_path.join("src/docs/contracts/");
-    tonic_build::configure()
+    let descriptor_path = Path::new(&std::env::var("OUT_DIR")?).join("descriptors.bin");
+    // Timestamp берётся из pbjson-types независимо от фичи `json`, чтобы фича не меняла
+    // типы полей сообщений: у него есть JSON-представление RFC 3339
+    let mut builder = tonic_build::configure()
         .build_client(true)
         .build_server(false)
+        .compile_well_known_types(true)
+        .extern_path(".google.protobuf", "::pbjson_types")
+        .out_dir(api_path);
+    if json_enabled() {
+        builder = builder.file_descriptor_set_path(&descriptor_path);
+    }
+    builder.compile(
+        &[
+            contracts_path.join("common.proto"),
+            contracts_path.join("instruments.proto"),
+            contracts_path.join("marketdata.proto"),
+            contracts_path.join("operations.proto"),
+            contracts_path.join("orders.proto"),
+            contracts_path.join("sandbox.proto"),
+            contracts_path.join("stoporders.proto"),
+            contracts_path.join("users.proto"),
+        ],
+        &[contracts_path],
+    )?;
+
+    #[cfg(feature = "json")]
+    generate_json_from_descriptors(api_path, &descriptor_path)?;
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+fn generate_json_from_descriptors(
+    api_path: &Path,
+    descriptor_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let descriptors = fs::read(descriptor_path)?;
+    pbjson_build::Builder::new()
+        .register_descriptors(&descriptors)?
         .out_dir(api_path)
-        .compile(
-            &[
-                contracts_path.join("common.proto"),
-                contracts_path.join("instruments.proto"),
-                contracts_path.join("marketdata.proto"),
-                contracts_path.join("operations.proto"),
-                contracts_path.join("orders.proto"),
-                contracts_path.join("sandbox.proto"),
-                contracts_path.join("stoporders.proto"),
-                contracts_path.join("users.proto"),
-            ],
-            &[contracts_path],
-        )
+        .build(&[".tinkoff"])?;
+    Ok(())
 }
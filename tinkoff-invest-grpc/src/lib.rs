@@ -18,10 +18,13 @@ pub mod decimal {
     pub use rust_decimal_macros::dec;
 }
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
 use std::error::Error;
 pub use tonic;
 
+/// Timestamp, который используют сгенерированные сообщения. Тип из pbjson-types:
+/// у него есть JSON-представление.
+pub use pbjson_types::Timestamp;
+
 use tonic::{
     codegen::InterceptedService,
     metadata::{Ascii, MetadataValue},
@@ -29,23 +32,99 @@ use tonic::{
     transport::{Channel, Endpoint},
 };
 
+const NANO_SCALE: u32 = 9;
+
+fn units_nano_to_decimal(units: i64, nano: i32) -> Decimal {
+    // |units| * 10^9 + |nano| < 2^96, поэтому переполнения не бывает
+    let mantissa = i128::from(units) * 1_000_000_000 + i128::from(nano);
+    Decimal::from_i128_with_scale(mantissa, NANO_SCALE).normalize()
+}
+
+impl From<&Quotation> for Decimal {
+    fn from(value: &Quotation) -> Self {
+        units_nano_to_decimal(value.units, value.nano)
+    }
+}
+
 impl From<Quotation> for Decimal {
     fn from(value: Quotation) -> Self {
-        Decimal::from(value.units) + Decimal::from(value.nano) / dec!(1_000_000_000)
+        (&value).into()
+    }
+}
+
+impl From<&MoneyValue> for Decimal {
+    fn from(value: &MoneyValue) -> Self {
+        units_nano_to_decimal(value.units, value.nano)
     }
 }
 
 impl From<MoneyValue> for (String, Decimal) {
     fn from(value: MoneyValue) -> Self {
-        (
-            value.currency,
-            Decimal::from(value.units) + Decimal::from(value.nano) / dec!(1_000_000_000)
-        )
+        let amount = (&value).into();
+        (value.currency, amount)
+    }
+}
+
+/// Ошибка перевода [`Decimal`] в [`Quotation`] или [`MoneyValue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalConversionError {
+    /// Больше 9 знаков после запятой: `nano` не может их хранить.
+    TooManyFractionalDigits,
+    /// Целая часть не помещается в `i64`.
+    Overflow,
+}
+
+impl std::fmt::Display for DecimalConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyFractionalDigits => write!(f, "more than 9 fractional digits"),
+            Self::Overflow => write!(f, "integer part does not fit into i64"),
+        }
+    }
+}
+
+impl Error for DecimalConversionError {}
+
+/// `units` и `nano` одного знака, как того требует API.
+fn decimal_to_units_nano(value: Decimal) -> Result<(i64, i32), DecimalConversionError> {
+    let value = value.normalize();
+    let scale = value.scale();
+    if scale > NANO_SCALE {
+        return Err(DecimalConversionError::TooManyFractionalDigits);
+    }
+    let nanos = value.mantissa() * 10i128.pow(NANO_SCALE - scale);
+    let units =
+        i64::try_from(nanos / 1_000_000_000).map_err(|_| DecimalConversionError::Overflow)?;
+    let nano = (nanos % 1_000_000_000) as i32;
+    Ok((units, nano))
+}
+
+impl TryFrom<Decimal> for Quotation {
+    type Error = DecimalConversionError;
+
+    fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+        let (units, nano) = decimal_to_units_nano(value)?;
+        Ok(Quotation { units, nano })
+    }
+}
+
+impl TryFrom<(String, Decimal)> for MoneyValue {
+    type Error = DecimalConversionError;
+
+    fn try_from((currency, value): (String, Decimal)) -> Result<Self, Self::Error> {
+        let (units, nano) = decimal_to_units_nano(value)?;
+        Ok(MoneyValue {
+            currency,
+            units,
+            nano,
+        })
     }
 }
 
 #[cfg(test)]
 mod decimal_tests {
+    use super::DecimalConversionError;
+    use proptest::prelude::*;
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
 
@@ -77,6 +156,73 @@ mod decimal_tests {
         let result: Decimal = value.into();
         assert_eq!(dec!(5.99), result);
     }
+
+    #[test]
+    fn convert_by_reference() {
+        let value = super::api::MoneyValue {
+            currency: "rub".to_owned(),
+            units: 1000,
+            nano: 500_000_000,
+        };
+        let result: Decimal = (&value).into();
+        assert_eq!(dec!(1000.5), result);
+        assert_eq!(result.to_string(), "1000.5");
+    }
+
+    #[test]
+    fn convert_negative_decimal() {
+        let value = super::api::Quotation::try_from(dec!(-5.99)).unwrap();
+        assert_eq!((value.units, value.nano), (-5, -990_000_000));
+        let value = super::api::Quotation::try_from(dec!(-0.5)).unwrap();
+        assert_eq!((value.units, value.nano), (0, -500_000_000));
+    }
+
+    #[test]
+    fn reject_unrepresentable_decimals() {
+        assert_eq!(
+            super::api::Quotation::try_from(dec!(0.0000000001)),
+            Err(DecimalConversionError::TooManyFractionalDigits)
+        );
+        assert_eq!(
+            super::api::Quotation::try_from(Decimal::MAX),
+            Err(DecimalConversionError::Overflow)
+        );
+        assert!(super::api::Quotation::try_from(dec!(1.5000000000)).is_ok());
+    }
+
+    fn units_nano() -> impl Strategy<Value = (i64, i32)> {
+        (any::<i64>(), 0..1_000_000_000i32).prop_map(|(units, nano)| {
+            // Знаки units и nano совпадают
+            if units < 0 {
+                (units, -nano)
+            } else {
+                (units, nano)
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn quotation_round_trip((units, nano) in units_nano()) {
+            let quotation = super::api::Quotation { units, nano };
+            let decimal: Decimal = (&quotation).into();
+            prop_assert_eq!(super::api::Quotation::try_from(decimal), Ok(quotation));
+        }
+
+        #[test]
+        fn money_value_round_trip((units, nano) in units_nano()) {
+            let money = super::api::MoneyValue { currency: "usd".to_owned(), units, nano };
+            let (currency, decimal): (String, Decimal) = money.clone().into();
+            prop_assert_eq!(super::api::MoneyValue::try_from((currency, decimal)), Ok(money));
+        }
+
+        #[test]
+        fn decimal_round_trip(mantissa in any::<i64>(), scale in 0..=9u32) {
+            let decimal = Decimal::new(mantissa, scale);
+            let quotation = super::api::Quotation::try_from(decimal).unwrap();
+            prop_assert_eq!(Decimal::from(quotation), decimal);
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -170,3 +316,41 @@ impl TinkoffInvestClient {
         StopOrdersServiceClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
     }
 }
+
+#[cfg(all(test, feature = "json"))]
+mod json_tests {
+    use super::api;
+
+    #[test]
+    fn serializes_canonical_json() {
+        let request = api::InstrumentRequest {
+            id_type: api::InstrumentIdType::Figi as i32,
+            class_code: String::new(),
+            id: "BBG004730N88".to_owned(),
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(json, r#"{"idType":"INSTRUMENT_ID_TYPE_FIGI","id":"BBG004730N88"}"#);
+        let parsed: api::InstrumentRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, request);
+
+        let quotation = api::Quotation {
+            units: 12,
+            nano: 500_000_000,
+        };
+        let json = serde_json::to_string(&quotation).unwrap();
+        assert_eq!(json, r#"{"units":"12","nano":500000000}"#);
+    }
+
+    #[test]
+    fn serializes_timestamp_as_rfc3339() {
+        let day = api::TradingDay {
+            date: Some(super::Timestamp {
+                seconds: 1_672_531_200,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&day).unwrap();
+        assert_eq!(json["date"], "2023-01-01T00:00:00Z");
+    }
+}
@@ -0,0 +1,501 @@
+//! Аналитика облигаций: грязная и чистая цена, доходность к погашению,
+//! текущая доходность, дюрация и выпуклость.
+//!
+//! Расчёты ведутся по графику купонов из [`InstrumentsClient::get_bond_coupons`](crate::instruments::InstrumentsClient::get_bond_coupons).
+//! Доходность — эффективная годовая, время считается как ACT/365.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use chrono::NaiveDate;
+use tinkoff_invest_grpc::decimal::rust_decimal::prelude::ToPrimitive;
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::types::{Aci, Bond, BondPrice, Coupon, CouponType, MoscowTime};
+
+const DAYS_IN_YEAR: f64 = 365.0;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-10;
+
+/// Что делать, если будущие выплаты по облигации заранее неизвестны.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstimationPolicy {
+    /// Вернуть ошибку.
+    Strict,
+    /// Оценить: неизвестный купон равен последнему известному,
+    /// а остаток номинала амортизируемой облигации гасится в дату погашения.
+    Estimate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CashFlowKind {
+    /// Купонная выплата.
+    Coupon,
+    /// Погашение номинала.
+    Redemption,
+}
+
+/// Выплата на одну облигацию.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CashFlow {
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub kind: CashFlowKind,
+    /// Сумма не известна заранее и была оценена.
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BondAnalyticsError {
+    /// У облигации не указан номинал.
+    MissingNominal,
+    /// У облигации нет даты погашения (например, бессрочная облигация).
+    MissingMaturityDate,
+    /// Облигация уже погашена на дату расчёта.
+    Matured,
+    /// Облигация с амортизацией: график погашения номинала неизвестен.
+    Amortization,
+    /// Размер купона ещё не определён (плавающий или переменный купон).
+    UnknownCoupon { coupon_number: i64 },
+    /// Цена должна быть положительной.
+    InvalidPrice,
+    /// Не удалось найти доходность.
+    NoSolution,
+}
+
+impl fmt::Display for BondAnalyticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingNominal => write!(f, "bond nominal is unknown"),
+            Self::MissingMaturityDate => write!(f, "bond has no maturity date"),
+            Self::Matured => write!(f, "bond is matured at settlement date"),
+            Self::Amortization => write!(f, "amortization schedule is unknown"),
+            Self::UnknownCoupon { coupon_number } => {
+                write!(f, "payment of coupon #{coupon_number} is unknown")
+            }
+            Self::InvalidPrice => write!(f, "price must be positive"),
+            Self::NoSolution => write!(f, "yield to maturity did not converge"),
+        }
+    }
+}
+
+impl std::error::Error for BondAnalyticsError {}
+
+pub type Result<T> = core::result::Result<T, BondAnalyticsError>;
+
+/// Грязная цена: чистая цена плюс НКД.
+pub fn dirty_price(clean_price: Decimal, accrued_interest: Decimal) -> Decimal {
+    clean_price + accrued_interest
+}
+
+/// Чистая цена: грязная цена минус НКД.
+pub fn clean_price(dirty_price: Decimal, accrued_interest: Decimal) -> Decimal {
+    dirty_price - accrued_interest
+}
+
+/// НКД на одну облигацию на дату расчётов, пропорционально прошедшей части купонного периода.
+///
+/// Возвращает `None`, если дата не попадает ни в один купонный период с известной выплатой.
+pub fn accrued_interest(coupons: &[Coupon], settlement: NaiveDate) -> Option<Decimal> {
+    coupons.iter().find_map(|coupon| {
+        let start = coupon.coupon_start_date()?.moscow_date();
+        let end = coupon.coupon_end_date()?.moscow_date();
+        if settlement < start || settlement >= end || coupon.coupon_period() <= 0 {
+            return None;
+        }
+        let payment = coupon.pay_one_bond()?.value();
+        let elapsed = (settlement - start).num_days();
+        Some(payment * Decimal::from(elapsed) / Decimal::from(coupon.coupon_period()))
+    })
+}
+
+/// Будущие выплаты на одну облигацию после даты расчётов, упорядоченные по дате.
+pub fn cash_flows(
+    bond: &Bond,
+    coupons: &[Coupon],
+    settlement: NaiveDate,
+    policy: EstimationPolicy,
+) -> Result<Vec<CashFlow>> {
+    let maturity = bond
+        .maturity_date()
+        .ok_or(BondAnalyticsError::MissingMaturityDate)?
+        .moscow_date();
+    if maturity <= settlement {
+        return Err(BondAnalyticsError::Matured);
+    }
+    // Выплаты в день расчётов уже не достаются покупателю
+    let first_day = settlement.succ_opt().unwrap_or(settlement);
+    cash_flows_between(bond, coupons, first_day..=maturity, policy)
+}
+
+/// Выплаты на одну облигацию с датами из интервала, упорядоченные по дате.
+///
+/// В отличие от [`cash_flows`], подходит и для бессрочных облигаций:
+/// погашение номинала включается, только если дата погашения известна и попадает в интервал.
+pub fn cash_flows_between(
+    bond: &Bond,
+    coupons: &[Coupon],
+    dates: RangeInclusive<NaiveDate>,
+    policy: EstimationPolicy,
+) -> Result<Vec<CashFlow>> {
+    let amortization = bond.with_amortization();
+    if amortization && policy == EstimationPolicy::Strict {
+        return Err(BondAnalyticsError::Amortization);
+    }
+    let maturity = bond.maturity_date().map(|date| date.moscow_date());
+
+    let mut coupons: Vec<&Coupon> = coupons.iter().collect();
+    coupons.sort_by_key(|c| c.coupon_date());
+
+    let mut last_known = None;
+    let mut flows = Vec::new();
+    for coupon in coupons {
+        let date = match coupon.coupon_date() {
+            Some(date) => date.moscow_date(),
+            None => continue,
+        };
+        let payment = coupon
+            .pay_one_bond()
+            .map(|p| p.value())
+            .filter(|p| !p.is_zero());
+        if payment.is_some() {
+            last_known = payment;
+        }
+        if !dates.contains(&date) || maturity.is_some_and(|maturity| date > maturity) {
+            continue;
+        }
+        let (amount, estimated) = match payment {
+            Some(amount) => (amount, false),
+            None if coupon.coupon_type() == CouponType::Discount => continue,
+            None => match (policy, last_known) {
+                (EstimationPolicy::Estimate, Some(amount)) => (amount, true),
+                _ => {
+                    return Err(BondAnalyticsError::UnknownCoupon {
+                        coupon_number: coupon.coupon_number(),
+                    })
+                }
+            },
+        };
+        flows.push(CashFlow {
+            date,
+            amount,
+            kind: CashFlowKind::Coupon,
+            estimated,
+        });
+    }
+
+    if let Some(maturity) = maturity.filter(|maturity| dates.contains(maturity)) {
+        let nominal = bond
+            .nominal()
+            .map(|n| n.value())
+            .ok_or(BondAnalyticsError::MissingNominal)?;
+        flows.push(CashFlow {
+            date: maturity,
+            amount: nominal,
+            kind: CashFlowKind::Redemption,
+            estimated: amortization,
+        });
+    }
+    Ok(flows)
+}
+
+/// Доходность к погашению (эффективная годовая) по грязной цене одной облигации.
+pub fn yield_to_maturity(
+    dirty_price: Decimal,
+    flows: &[CashFlow],
+    settlement: NaiveDate,
+) -> Result<f64> {
+    let price = positive(dirty_price)?;
+    let flows = discounting_terms(flows, settlement);
+    if flows.is_empty() {
+        return Err(BondAnalyticsError::Matured);
+    }
+
+    let npv = |y: f64| -> f64 {
+        flows
+            .iter()
+            .map(|(t, cf)| cf / (1.0 + y).powf(*t))
+            .sum::<f64>()
+            - price
+    };
+    let derivative = |y: f64| -> f64 {
+        flows
+            .iter()
+            .map(|(t, cf)| -t * cf / (1.0 + y).powf(t + 1.0))
+            .sum::<f64>()
+    };
+
+    // Метод Ньютона, а если он не сошёлся — бисекция
+    let mut y = 0.1;
+    for _ in 0..MAX_ITERATIONS {
+        let value = npv(y);
+        if value.abs() < TOLERANCE {
+            return Ok(y);
+        }
+        let slope = derivative(y);
+        if slope == 0.0 || !slope.is_finite() {
+            break;
+        }
+        let next = y - value / slope;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        y = next;
+    }
+
+    let (mut low, mut high) = (-0.99, 10.0);
+    if npv(low).signum() == npv(high).signum() {
+        return Err(BondAnalyticsError::NoSolution);
+    }
+    for _ in 0..MAX_ITERATIONS * 10 {
+        let middle = (low + high) / 2.0;
+        let value = npv(middle);
+        if value.abs() < TOLERANCE || (high - low) / 2.0 < TOLERANCE {
+            return Ok(middle);
+        }
+        if value.signum() == npv(low).signum() {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Err(BondAnalyticsError::NoSolution)
+}
+
+/// Текущая доходность: годовой купонный доход, делённый на чистую цену.
+/// Годовой доход оценивается по ближайшему купону.
+pub fn current_yield(
+    clean_price: Decimal,
+    flows: &[CashFlow],
+    coupon_quantity_per_year: u32,
+) -> Result<f64> {
+    let price = positive(clean_price)?;
+    let next_coupon = flows
+        .iter()
+        .find(|f| f.kind == CashFlowKind::Coupon)
+        .and_then(|f| f.amount.to_f64())
+        .unwrap_or(0.0);
+    Ok(next_coupon * f64::from(coupon_quantity_per_year) / price)
+}
+
+/// Дюрация Маколея в годах.
+pub fn macaulay_duration(flows: &[CashFlow], ytm: f64, settlement: NaiveDate) -> f64 {
+    let flows = discounting_terms(flows, settlement);
+    let (weighted, price) = flows.iter().fold((0.0, 0.0), |(weighted, price), (t, cf)| {
+        let pv = cf / (1.0 + ytm).powf(*t);
+        (weighted + t * pv, price + pv)
+    });
+    weighted / price
+}
+
+/// Модифицированная дюрация: относительное изменение цены при изменении доходности на единицу.
+pub fn modified_duration(macaulay_duration: f64, ytm: f64) -> f64 {
+    macaulay_duration / (1.0 + ytm)
+}
+
+/// Выпуклость в годах в квадрате.
+pub fn convexity(flows: &[CashFlow], ytm: f64, settlement: NaiveDate) -> f64 {
+    let flows = discounting_terms(flows, settlement);
+    let (weighted, price) = flows.iter().fold((0.0, 0.0), |(weighted, price), (t, cf)| {
+        let pv = cf / (1.0 + ytm).powf(*t);
+        (weighted + t * (t + 1.0) * pv, price + pv)
+    });
+    weighted / (price * (1.0 + ytm).powi(2))
+}
+
+/// Сводные показатели облигации.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondAnalytics {
+    /// Чистая цена одной облигации в валюте номинала.
+    pub clean_price: Decimal,
+    /// НКД на одну облигацию.
+    pub accrued_interest: Decimal,
+    /// Грязная цена одной облигации.
+    pub dirty_price: Decimal,
+    pub yield_to_maturity: f64,
+    pub current_yield: f64,
+    pub macaulay_duration: f64,
+    pub modified_duration: f64,
+    pub convexity: f64,
+    /// Часть выплат была оценена, см. [`EstimationPolicy::Estimate`].
+    pub estimated: bool,
+    pub cash_flows: Vec<CashFlow>,
+}
+
+impl BondAnalytics {
+    /// Рассчитать показатели по котировке.
+    ///
+    /// НКД считается по графику купонов на дату расчётов, а если это невозможно —
+    /// берётся текущий НКД из [`Bond::aci_value`].
+    pub fn calculate(
+        bond: &Bond,
+        coupons: &[Coupon],
+        price: BondPrice,
+        settlement: NaiveDate,
+        policy: EstimationPolicy,
+    ) -> Result<Self> {
+        let clean = bond
+            .money_per_bond(price, Aci::Excluded)
+            .ok_or(BondAnalyticsError::MissingNominal)?;
+        let accrued = accrued_interest(coupons, settlement)
+            .or_else(|| bond.aci_value().map(|aci| aci.value()))
+            .unwrap_or_default();
+        let dirty = dirty_price(clean, accrued);
+
+        let flows = cash_flows(bond, coupons, settlement, policy)?;
+        let ytm = yield_to_maturity(dirty, &flows, settlement)?;
+        let macaulay = macaulay_duration(&flows, ytm, settlement);
+        Ok(Self {
+            clean_price: clean,
+            accrued_interest: accrued,
+            dirty_price: dirty,
+            yield_to_maturity: ytm,
+            current_yield: current_yield(clean, &flows, bond.coupon_quantity_per_year())?,
+            macaulay_duration: macaulay,
+            modified_duration: modified_duration(macaulay, ytm),
+            convexity: convexity(&flows, ytm, settlement),
+            estimated: flows.iter().any(|f| f.estimated),
+            cash_flows: flows,
+        })
+    }
+}
+
+fn positive(price: Decimal) -> Result<f64> {
+    match price.to_f64() {
+        Some(price) if price > 0.0 => Ok(price),
+        _ => Err(BondAnalyticsError::InvalidPrice),
+    }
+}
+
+/// Пары (срок в годах, сумма) для выплат после даты расчётов.
+fn discounting_terms(flows: &[CashFlow], settlement: NaiveDate) -> Vec<(f64, f64)> {
+    flows
+        .iter()
+        .filter(|f| f.date > settlement)
+        .map(|f| {
+            let years = (f.date - settlement).num_days() as f64 / DAYS_IN_YEAR;
+            (years, f.amount.to_f64().unwrap_or(0.0))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TimestampBound;
+    use tinkoff_invest_grpc::api;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn timestamp(date: NaiveDate) -> tinkoff_invest_grpc::Timestamp {
+        crate::types::chrono_timestamp_to_grpc_timestamp(date.to_utc())
+    }
+
+    fn rub(units: i64) -> api::MoneyValue {
+        api::MoneyValue {
+            currency: "rub".to_owned(),
+            units,
+            nano: 0,
+        }
+    }
+
+    fn bond(amortization: bool) -> Bond {
+        api::Bond {
+            nominal: Some(rub(1000)),
+            maturity_date: Some(timestamp(date(2023, 1, 1))),
+            coupon_quantity_per_year: 1,
+            amortization_flag: amortization,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    fn coupon(number: i64, paid: NaiveDate, payment: Option<i64>, kind: api::CouponType) -> Coupon {
+        api::Coupon {
+            coupon_number: number,
+            coupon_date: Some(timestamp(paid)),
+            coupon_start_date: Some(timestamp(paid - chrono::Duration::days(365))),
+            coupon_end_date: Some(timestamp(paid)),
+            coupon_period: 365,
+            pay_one_bond: payment.map(rub),
+            coupon_type: kind as i32,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn par_bond_yields_its_coupon_rate() {
+        let coupons = [
+            coupon(1, date(2022, 1, 1), Some(100), api::CouponType::Constant),
+            coupon(2, date(2023, 1, 1), Some(100), api::CouponType::Constant),
+        ];
+        let analytics = BondAnalytics::calculate(
+            &bond(false),
+            &coupons,
+            BondPrice::from_percent(Decimal::from(100)),
+            date(2021, 1, 1),
+            EstimationPolicy::Strict,
+        )
+        .unwrap();
+
+        assert_eq!(analytics.dirty_price, Decimal::from(1000));
+        assert!((analytics.yield_to_maturity - 0.1).abs() < 1e-9);
+        assert!((analytics.current_yield - 0.1).abs() < 1e-9);
+        assert!((analytics.macaulay_duration - 1.909_090_909).abs() < 1e-6);
+        assert!((analytics.modified_duration - 1.735_537_190).abs() < 1e-6);
+        assert!((analytics.convexity - 4.658_151_765).abs() < 1e-6);
+    }
+
+    #[test]
+    fn accrued_interest_is_proportional_to_elapsed_period() {
+        let coupons = [coupon(
+            1,
+            date(2022, 1, 1),
+            Some(100),
+            api::CouponType::Constant,
+        )];
+        let accrued = accrued_interest(&coupons, date(2021, 7, 2)).unwrap();
+        assert_eq!(accrued.round_dp(2), Decimal::new(4986, 2));
+    }
+
+    #[test]
+    fn unknown_floating_coupon_is_estimated_only_on_request() {
+        let coupons = [
+            coupon(1, date(2022, 1, 1), Some(100), api::CouponType::Floating),
+            coupon(2, date(2023, 1, 1), None, api::CouponType::Floating),
+        ];
+        let settlement = date(2021, 1, 1);
+        assert_eq!(
+            cash_flows(&bond(false), &coupons, settlement, EstimationPolicy::Strict),
+            Err(BondAnalyticsError::UnknownCoupon { coupon_number: 2 })
+        );
+
+        let flows = cash_flows(
+            &bond(false),
+            &coupons,
+            settlement,
+            EstimationPolicy::Estimate,
+        )
+        .unwrap();
+        assert_eq!(flows.len(), 3);
+        assert_eq!(flows[1].amount, Decimal::from(100));
+        assert!(flows[1].estimated);
+    }
+
+    #[test]
+    fn amortization_is_rejected_in_strict_mode() {
+        let settlement = date(2021, 1, 1);
+        assert_eq!(
+            cash_flows(&bond(true), &[], settlement, EstimationPolicy::Strict),
+            Err(BondAnalyticsError::Amortization)
+        );
+        let flows = cash_flows(&bond(true), &[], settlement, EstimationPolicy::Estimate).unwrap();
+        assert_eq!(flows[0].kind, CashFlowKind::Redemption);
+        assert!(flows[0].estimated);
+    }
+}
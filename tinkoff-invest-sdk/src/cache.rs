@@ -0,0 +1,608 @@
+//! Кеширование ответов для методов, возвращающих редко меняющиеся справочные данные.
+//!
+//! Ответы хранятся в виде закодированных protobuf-сообщений, а ключом служит
+//! закодированный запрос, поэтому разные параметры запроса кешируются независимо.
+//!
+//! ```no_run
+//! # async fn run(client: tinkoff_invest_sdk::TinkoffInvestClient) -> tinkoff_invest_sdk::Result<()> {
+//! use tinkoff_invest_sdk::cache::{Cache, CachePolicy, MemoryStorage};
+//! use tinkoff_invest_sdk::types::InstrumentsList;
+//!
+//! let cache = Cache::new(MemoryStorage::new(), CachePolicy::default());
+//! let mut instruments = client.instruments().with_cache(cache.clone());
+//! let bonds = instruments.bonds(InstrumentsList::All).await?; // запрос к API
+//! let bonds = instruments.bonds(InstrumentsList::All).await?; // из кеша
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::ops::{Deref, DerefMut, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use prost::Message;
+use tinkoff_invest_grpc::api;
+
+use crate::instruments::{not_found_as_none, InstrumentRequest, InstrumentsClient};
+use crate::shared::{date_range_to_timestamp_pair, EasyConvert};
+use crate::types::{self, Figi, InstrumentsList, TimestampBound};
+use crate::users::UsersClient;
+
+/// Кешируемые методы API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachedMethod {
+    TradingSchedules,
+    Bonds,
+    BondBy,
+    BondCoupons,
+    Accounts,
+    UserTariff,
+    UserInfo,
+}
+
+impl CachedMethod {
+    /// Имя метода, под которым хранятся его записи.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CachedMethod::TradingSchedules => "TradingSchedules",
+            CachedMethod::Bonds => "Bonds",
+            CachedMethod::BondBy => "BondBy",
+            CachedMethod::BondCoupons => "GetBondCoupons",
+            CachedMethod::Accounts => "GetAccounts",
+            CachedMethod::UserTariff => "GetUserTariff",
+            CachedMethod::UserInfo => "GetInfo",
+        }
+    }
+}
+
+/// Время жизни записей для каждого метода.
+/// Методы без заданного TTL не кешируются.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    ttls: HashMap<CachedMethod, Duration>,
+}
+
+impl CachePolicy {
+    /// Политика, в которой ничего не кешируется.
+    pub fn empty() -> Self {
+        Self {
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Задать время жизни записей для метода.
+    pub fn ttl(mut self, method: CachedMethod, ttl: Duration) -> Self {
+        self.ttls.insert(method, ttl);
+        self
+    }
+
+    /// Отключить кеширование метода.
+    pub fn disable(mut self, method: CachedMethod) -> Self {
+        self.ttls.remove(&method);
+        self
+    }
+
+    pub fn ttl_of(&self, method: CachedMethod) -> Option<Duration> {
+        self.ttls.get(&method).copied()
+    }
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        Self::empty()
+            .ttl(
+                CachedMethod::TradingSchedules,
+                Duration::from_secs(6 * HOUR),
+            )
+            .ttl(CachedMethod::Bonds, Duration::from_secs(HOUR))
+            .ttl(CachedMethod::BondBy, Duration::from_secs(HOUR))
+            .ttl(CachedMethod::BondCoupons, Duration::from_secs(6 * HOUR))
+            .ttl(CachedMethod::Accounts, Duration::from_secs(10 * MINUTE))
+            .ttl(CachedMethod::UserTariff, Duration::from_secs(10 * MINUTE))
+            .ttl(CachedMethod::UserInfo, Duration::from_secs(10 * MINUTE))
+    }
+}
+
+/// Запись кеша.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// Момент, после которого запись считается устаревшей.
+    pub expires_at: SystemTime,
+    /// Закодированный protobuf-ответ.
+    pub data: Vec<u8>,
+}
+
+impl CacheEntry {
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Хранилище записей кеша.
+///
+/// Кеш не должен ломать запросы, поэтому ошибки хранилища не пробрасываются:
+/// нечитаемая запись считается отсутствующей, а неудачная запись игнорируется.
+pub trait CacheStorage: Send + Sync {
+    fn get(&self, method: &str, key: &str) -> Option<CacheEntry>;
+    fn put(&self, method: &str, key: &str, entry: CacheEntry);
+    fn remove(&self, method: &str, key: &str);
+    /// Удалить все записи метода.
+    fn clear_method(&self, method: &str);
+    /// Удалить все записи.
+    fn clear(&self);
+}
+
+/// Хранилище в памяти процесса.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<HashMap<(String, String), CacheEntry>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CacheStorage for MemoryStorage {
+    fn get(&self, method: &str, key: &str) -> Option<CacheEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(&(method.to_owned(), key.to_owned())).cloned()
+    }
+
+    fn put(&self, method: &str, key: &str, entry: CacheEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert((method.to_owned(), key.to_owned()), entry);
+    }
+
+    fn remove(&self, method: &str, key: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(&(method.to_owned(), key.to_owned()));
+    }
+
+    fn clear_method(&self, method: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(m, _), _| m != method);
+    }
+
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Хранилище на диске: по каталогу на метод и по файлу на запись.
+///
+/// Файл содержит время истечения (секунды unix time, 8 байт big-endian), за которым следует ответ.
+#[derive(Debug, Clone)]
+pub struct DiskStorage {
+    root: PathBuf,
+}
+
+impl DiskStorage {
+    pub fn new(root: impl Into<PathBuf>) -> io::Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn entry_path(&self, method: &str, key: &str) -> PathBuf {
+        self.root.join(method).join(key)
+    }
+
+    fn read(&self, method: &str, key: &str) -> io::Result<CacheEntry> {
+        let bytes = fs::read(self.entry_path(method, key))?;
+        if bytes.len() < 8 {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        let (header, data) = bytes.split_at(8);
+        let seconds = u64::from_be_bytes(header.try_into().unwrap());
+        Ok(CacheEntry {
+            expires_at: UNIX_EPOCH + Duration::from_secs(seconds),
+            data: data.to_vec(),
+        })
+    }
+
+    fn write(&self, method: &str, key: &str, entry: &CacheEntry) -> io::Result<()> {
+        fs::create_dir_all(self.root.join(method))?;
+        let seconds = entry
+            .expires_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut bytes = Vec::with_capacity(8 + entry.data.len());
+        bytes.extend_from_slice(&seconds.to_be_bytes());
+        bytes.extend_from_slice(&entry.data);
+        fs::write(self.entry_path(method, key), bytes)
+    }
+}
+
+impl CacheStorage for DiskStorage {
+    fn get(&self, method: &str, key: &str) -> Option<CacheEntry> {
+        self.read(method, key).ok()
+    }
+
+    fn put(&self, method: &str, key: &str, entry: CacheEntry) {
+        let _ = self.write(method, key, &entry);
+    }
+
+    fn remove(&self, method: &str, key: &str) {
+        let _ = fs::remove_file(self.entry_path(method, key));
+    }
+
+    fn clear_method(&self, method: &str) {
+        let _ = fs::remove_dir_all(self.root.join(method));
+    }
+
+    fn clear(&self) {
+        if let Ok(dirs) = fs::read_dir(&self.root) {
+            for dir in dirs.flatten() {
+                let _ = fs::remove_dir_all(dir.path());
+            }
+        }
+    }
+}
+
+/// Кеш ответов. Дешево клонируется, клоны разделяют одно хранилище.
+#[derive(Clone)]
+pub struct Cache {
+    storage: Arc<dyn CacheStorage>,
+    policy: Arc<CachePolicy>,
+}
+
+impl Cache {
+    pub fn new(storage: impl CacheStorage + 'static, policy: CachePolicy) -> Self {
+        Self {
+            storage: Arc::new(storage),
+            policy: Arc::new(policy),
+        }
+    }
+
+    pub fn policy(&self) -> &CachePolicy {
+        &self.policy
+    }
+
+    /// Сбросить все записи метода.
+    pub fn invalidate(&self, method: CachedMethod) {
+        self.storage.clear_method(method.name());
+    }
+
+    /// Сбросить запись для конкретного запроса.
+    pub fn invalidate_request(&self, method: CachedMethod, request: &impl Message) {
+        self.storage.remove(method.name(), &request_key(request));
+    }
+
+    /// Сбросить весь кеш.
+    pub fn invalidate_all(&self) {
+        self.storage.clear();
+    }
+
+    pub(crate) async fn get_or_fetch<Req, Resp, Fut>(
+        &self,
+        method: CachedMethod,
+        request: Req,
+        fetch: impl FnOnce(Req) -> Fut,
+    ) -> crate::Result<Resp>
+    where
+        Req: Message,
+        Resp: Message + Default,
+        Fut: Future<Output = crate::Result<Resp>>,
+    {
+        let ttl = match self.policy.ttl_of(method) {
+            Some(ttl) => ttl,
+            None => return fetch(request).await,
+        };
+        let name = method.name();
+        let key = request_key(&request);
+        let now = SystemTime::now();
+        if let Some(entry) = self.storage.get(name, &key) {
+            if !entry.is_expired(now) {
+                if let Ok(response) = Resp::decode(entry.data.as_slice()) {
+                    return Ok(response);
+                }
+            }
+            self.storage.remove(name, &key);
+        }
+
+        let response = fetch(request).await?;
+        let entry = CacheEntry {
+            expires_at: now + ttl,
+            data: response.encode_to_vec(),
+        };
+        self.storage.put(name, &key, entry);
+        Ok(response)
+    }
+}
+
+fn request_key(request: &impl Message) -> String {
+    let bytes = request.encode_to_vec();
+    if bytes.is_empty() {
+        // У запросов без параметров пустое тело, а пустое имя файла недопустимо
+        return "_".to_owned();
+    }
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// [`InstrumentsClient`] с кешированием справочных методов.
+/// Некешируемые методы доступны через `Deref`.
+pub struct CachedInstrumentsClient {
+    client: InstrumentsClient,
+    cache: Cache,
+}
+
+impl CachedInstrumentsClient {
+    pub(crate) fn new(client: InstrumentsClient, cache: Cache) -> Self {
+        Self { client, cache }
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn into_inner(self) -> InstrumentsClient {
+        self.client
+    }
+
+    pub async fn bond_by(
+        &mut self,
+        request: InstrumentRequest,
+    ) -> crate::Result<Option<types::Bond>> {
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(CachedMethod::BondBy, request.into(), |req| {
+                client.bond_by_raw(req)
+            })
+            .await;
+        Ok(not_found_as_none(response)?.and_then(|r| r.instrument.map(Into::into)))
+    }
+
+    pub async fn trading_schedules_all<T: TimestampBound>(
+        &mut self,
+        range: impl RangeBounds<T>,
+    ) -> crate::Result<Vec<types::TradingSchedule>> {
+        self.trading_schedules(String::new(), range).await
+    }
+
+    pub async fn trading_schedules<T: TimestampBound>(
+        &mut self,
+        exchange: String,
+        range: impl RangeBounds<T>,
+    ) -> crate::Result<Vec<types::TradingSchedule>> {
+        let (from, to) = date_range_to_timestamp_pair(range);
+        let req = api::TradingSchedulesRequest { exchange, from, to };
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(CachedMethod::TradingSchedules, req, |req| {
+                client.trading_schedules_raw(req)
+            })
+            .await?;
+        Ok(response.exchanges.convert())
+    }
+
+    pub async fn bonds(&mut self, list: InstrumentsList) -> crate::Result<Vec<types::Bond>> {
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(CachedMethod::Bonds, list.into(), |req| {
+                client.bonds_raw(req)
+            })
+            .await?;
+        Ok(response.instruments.convert())
+    }
+
+    pub async fn get_bond_coupons<T: TimestampBound>(
+        &mut self,
+        figi: &Figi,
+        range: impl RangeBounds<T>,
+    ) -> crate::Result<Vec<types::Coupon>> {
+        let (from, to) = date_range_to_timestamp_pair(range);
+        let req = api::GetBondCouponsRequest {
+            figi: figi.to_string(),
+            from,
+            to,
+        };
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(CachedMethod::BondCoupons, req, |req| {
+                client.get_bond_coupons_raw(req)
+            })
+            .await?;
+        Ok(response.events.convert())
+    }
+}
+
+impl Deref for CachedInstrumentsClient {
+    type Target = InstrumentsClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+impl DerefMut for CachedInstrumentsClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.client
+    }
+}
+
+/// [`UsersClient`] с кешированием справочных методов.
+/// Некешируемые методы доступны через `Deref`.
+pub struct CachedUsersClient {
+    client: UsersClient,
+    cache: Cache,
+}
+
+impl CachedUsersClient {
+    pub(crate) fn new(client: UsersClient, cache: Cache) -> Self {
+        Self { client, cache }
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn into_inner(self) -> UsersClient {
+        self.client
+    }
+
+    pub async fn get_accounts(&mut self) -> crate::Result<Vec<types::Account>> {
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(CachedMethod::Accounts, api::GetAccountsRequest {}, |req| {
+                client.get_accounts_raw(req)
+            })
+            .await?;
+        Ok(response.accounts.convert())
+    }
+
+    pub async fn get_user_tariff(&mut self) -> crate::Result<types::UserTariff> {
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(
+                CachedMethod::UserTariff,
+                api::GetUserTariffRequest {},
+                |req| client.get_user_tariff_raw(req),
+            )
+            .await?;
+        Ok(response.into())
+    }
+
+    pub async fn get_info(&mut self) -> crate::Result<types::UserInfo> {
+        let client = &mut self.client;
+        let response = self
+            .cache
+            .get_or_fetch(CachedMethod::UserInfo, api::GetInfoRequest {}, |req| {
+                client.get_info_raw(req)
+            })
+            .await?;
+        Ok(response.into())
+    }
+}
+
+impl Deref for CachedUsersClient {
+    type Target = UsersClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+impl DerefMut for CachedUsersClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(expires_at: SystemTime, data: &[u8]) -> CacheEntry {
+        CacheEntry {
+            expires_at,
+            data: data.to_vec(),
+        }
+    }
+
+    fn check_storage(storage: &dyn CacheStorage) {
+        let expires_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        storage.put("Bonds", "01", entry(expires_at, b"bonds"));
+        storage.put("Bonds", "02", entry(expires_at, b"all bonds"));
+        storage.put("GetInfo", "_", entry(expires_at, b"info"));
+        assert_eq!(
+            storage.get("Bonds", "01"),
+            Some(entry(expires_at, b"bonds"))
+        );
+
+        storage.remove("Bonds", "01");
+        assert_eq!(storage.get("Bonds", "01"), None);
+
+        storage.clear_method("Bonds");
+        assert_eq!(storage.get("Bonds", "02"), None);
+        assert!(storage.get("GetInfo", "_").is_some());
+
+        storage.clear();
+        assert_eq!(storage.get("GetInfo", "_"), None);
+    }
+
+    #[test]
+    fn memory_storage() {
+        check_storage(&MemoryStorage::new());
+    }
+
+    #[test]
+    fn disk_storage() {
+        let root =
+            std::env::temp_dir().join(format!("tinkoff-invest-cache-{}", std::process::id()));
+        let storage = DiskStorage::new(&root).unwrap();
+        check_storage(&storage);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fetches_once_until_invalidated() {
+        let cache = Cache::new(MemoryStorage::new(), CachePolicy::default());
+        let request = api::InstrumentsRequest {
+            instrument_status: api::InstrumentStatus::All as i32,
+        };
+        let mut calls = 0;
+        for _ in 0..3 {
+            let response: api::GetInfoResponse = cache
+                .get_or_fetch(CachedMethod::Bonds, request.clone(), |_| {
+                    calls += 1;
+                    async {
+                        Ok(api::GetInfoResponse {
+                            tariff: "investor".to_owned(),
+                            ..Default::default()
+                        })
+                    }
+                })
+                .await
+                .unwrap();
+            assert_eq!(response.tariff, "investor");
+        }
+        assert_eq!(calls, 1);
+
+        cache.invalidate(CachedMethod::Bonds);
+        let _: api::GetInfoResponse = cache
+            .get_or_fetch(CachedMethod::Bonds, request, |_| {
+                calls += 1;
+                async { Ok(api::GetInfoResponse::default()) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(calls, 2);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_refetched() {
+        let policy = CachePolicy::empty().ttl(CachedMethod::UserInfo, Duration::ZERO);
+        let cache = Cache::new(MemoryStorage::new(), policy);
+        let mut calls = 0;
+        for _ in 0..2 {
+            let _: api::GetInfoResponse = cache
+                .get_or_fetch(CachedMethod::UserInfo, api::GetInfoRequest {}, |_| {
+                    calls += 1;
+                    async { Ok(api::GetInfoResponse::default()) }
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(calls, 2);
+    }
+}
@@ -0,0 +1,466 @@
+//! Локальное хранилище свечей в SQLite.
+//!
+//! Свечи хранятся по uid инструмента и интервалу. Хранилище помнит, за какие интервалы времени
+//! свечи уже загружены, поэтому [`CandleStore::sync`] запрашивает у API только недостающее.
+
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::TryStreamExt;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, Row};
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::candle_validation::find_gaps;
+use crate::error::TinkoffInvestError;
+use crate::market_data::MarketDataClient;
+use crate::trading_calendar::TradingCalendar;
+use crate::types::{moscow_day_start, Candle, CandleInterval, InstrumentUid, MoscowTime};
+
+/// Через сколько после конца интервала свеча точно есть в API.
+const PUBLICATION_LAG_SECONDS: i64 = 10;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS candles (
+    instrument_uid TEXT NOT NULL,
+    interval INTEGER NOT NULL,
+    time INTEGER NOT NULL,
+    open TEXT NOT NULL,
+    high TEXT NOT NULL,
+    low TEXT NOT NULL,
+    close TEXT NOT NULL,
+    volume INTEGER NOT NULL,
+    is_complete INTEGER NOT NULL,
+    PRIMARY KEY (instrument_uid, interval, time)
+);
+CREATE TABLE IF NOT EXISTS synced_ranges (
+    instrument_uid TEXT NOT NULL,
+    interval INTEGER NOT NULL,
+    start INTEGER NOT NULL,
+    end INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS synced_ranges_key ON synced_ranges (instrument_uid, interval);
+";
+
+/// Хранилище свечей. Время хранится в миллисекундах Unix, цены — строками без потери точности.
+pub struct CandleStore {
+    connection: Connection,
+}
+
+impl CandleStore {
+    /// Открыть базу в файле, создав таблицы при необходимости.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CandleStoreError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Временная база в памяти.
+    pub fn open_in_memory() -> Result<Self, CandleStoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, CandleStoreError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Сохранить свечи. Свеча с тем же временем заменяется: несформированная свеча обновится
+    /// при следующей загрузке.
+    pub fn insert(
+        &mut self,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+        candles: &[Candle],
+    ) -> Result<(), CandleStoreError> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO candles
+                 (instrument_uid, interval, time, open, high, low, close, volume, is_complete)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for candle in candles {
+                statement.execute(params![
+                    instrument.as_str(),
+                    i32::from(interval),
+                    candle.time.timestamp_millis(),
+                    candle.open.to_string(),
+                    candle.high.to_string(),
+                    candle.low.to_string(),
+                    candle.close.to_string(),
+                    candle.volume,
+                    candle.is_complete,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Свечи из хранилища за интервал по возрастанию времени.
+    pub fn candles(
+        &self,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<impl Iterator<Item = Candle>, CandleStoreError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT time, open, high, low, close, volume, is_complete FROM candles
+             WHERE instrument_uid = ?1 AND interval = ?2 AND time >= ?3 AND time < ?4
+             ORDER BY time",
+        )?;
+        let candles = statement
+            .query_map(
+                params![
+                    instrument.as_str(),
+                    i32::from(interval),
+                    range.start.timestamp_millis(),
+                    range.end.timestamp_millis(),
+                ],
+                candle_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(candles.into_iter())
+    }
+
+    /// Части интервала, за которые свечи ещё не загружались.
+    pub fn missing_ranges(
+        &self,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Range<DateTime<Utc>>>, CandleStoreError> {
+        let synced = self.synced_ranges(instrument, interval)?;
+        let range = range.start.timestamp_millis()..range.end.timestamp_millis();
+        // Границы недостающих частей лежат внутри запрошенного интервала, поэтому корректны.
+        Ok(subtract_ranges(range, &synced)
+            .into_iter()
+            .filter_map(|range| Some(from_millis(range.start)?..from_millis(range.end)?))
+            .collect())
+    }
+
+    /// Догрузить из API свечи за ещё не загруженные части интервала. Возвращает число
+    /// полученных свечей.
+    ///
+    /// Загруженным считается интервал до начала текущей свечи (с небольшим запасом на задержку
+    /// публикации), но не дальше начала несформированной свечи: если в текущем интервале
+    /// ещё не было сделок, свечи за него нет, и его загрузит следующая синхронизация.
+    pub async fn sync(
+        &mut self,
+        client: &MarketDataClient,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<usize, CandleStoreError> {
+        let range = range.start..range.end.min(synced_until(Utc::now(), interval));
+        let mut received = 0;
+        for missing in self.missing_ranges(instrument, interval, range)? {
+            let candles: Vec<Candle> = client
+                .candles_history(instrument.clone(), interval, missing.clone())
+                .try_collect()
+                .await?;
+            self.insert(instrument, interval, &candles)?;
+            received += candles.len();
+            let end = candles
+                .iter()
+                .filter(|candle| !candle.is_complete)
+                .map(|candle| candle.time)
+                .min()
+                .map_or(missing.end, |time| time.clamp(missing.start, missing.end));
+            self.mark_synced(instrument, interval, missing.start..end)?;
+        }
+        Ok(received)
+    }
+
+    /// Пропуски в загруженных свечах: интервалы торговых сессий календаря (для дневных свечей —
+    /// торговые дни), за которые в хранилище нет ни одной свечи. Пропуски ищутся так же, как в
+    /// [`validate_candles`](crate::candle_validation::validate_candles).
+    pub fn gaps(
+        &self,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+        calendar: &TradingCalendar,
+    ) -> Result<Vec<Range<DateTime<Utc>>>, CandleStoreError> {
+        let times: Vec<DateTime<Utc>> = self
+            .candles(instrument, interval, range.clone())?
+            .map(|candle| candle.time)
+            .collect();
+        Ok(find_gaps(&times, interval, range, calendar))
+    }
+
+    fn synced_ranges(
+        &self,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+    ) -> Result<Vec<Range<i64>>, CandleStoreError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT start, end FROM synced_ranges
+             WHERE instrument_uid = ?1 AND interval = ?2 ORDER BY start",
+        )?;
+        let ranges = statement
+            .query_map(params![instrument.as_str(), i32::from(interval)], |row| {
+                Ok(row.get(0)?..row.get(1)?)
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(ranges)
+    }
+
+    fn mark_synced(
+        &mut self,
+        instrument: &InstrumentUid,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<(), CandleStoreError> {
+        let mut ranges = self.synced_ranges(instrument, interval)?;
+        ranges.push(range.start.timestamp_millis()..range.end.timestamp_millis());
+        let ranges = merge_ranges(ranges);
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM synced_ranges WHERE instrument_uid = ?1 AND interval = ?2",
+            params![instrument.as_str(), i32::from(interval)],
+        )?;
+        for range in ranges {
+            transaction.execute(
+                "INSERT INTO synced_ranges (instrument_uid, interval, start, end)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    instrument.as_str(),
+                    i32::from(interval),
+                    range.start,
+                    range.end
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+/// Начало интервала свечи, в котором был момент `now` с поправкой на задержку публикации:
+/// всё, что раньше, уже не изменится.
+fn synced_until(now: DateTime<Utc>, interval: CandleInterval) -> DateTime<Utc> {
+    let time = now - Duration::seconds(PUBLICATION_LAG_SECONDS);
+    if interval == CandleInterval::Day {
+        return moscow_day_start(time.moscow_date());
+    }
+    match interval.duration() {
+        Some(step) => {
+            let millis = time.timestamp_millis();
+            from_millis(millis - millis.rem_euclid(step.num_milliseconds())).unwrap_or(time)
+        }
+        None => time,
+    }
+}
+
+fn candle_from_row(row: &Row<'_>) -> rusqlite::Result<Candle> {
+    let decimal = |index: usize| -> rusqlite::Result<Decimal> {
+        let value: String = row.get(index)?;
+        Decimal::from_str(&value).map_err(|error| {
+            rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(error))
+        })
+    };
+    let millis: i64 = row.get(0)?;
+    let time = from_millis(millis).ok_or_else(|| {
+        let error = format!("invalid timestamp {}", millis);
+        rusqlite::Error::FromSqlConversionFailure(0, Type::Integer, error.into())
+    })?;
+    Ok(Candle {
+        time,
+        open: decimal(1)?,
+        high: decimal(2)?,
+        low: decimal(3)?,
+        close: decimal(4)?,
+        volume: row.get(5)?,
+        is_complete: row.get(6)?,
+    })
+}
+
+fn from_millis(millis: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+/// Объединить пересекающиеся и соседние интервалы.
+fn merge_ranges(mut ranges: Vec<Range<i64>>) -> Vec<Range<i64>> {
+    ranges.retain(|range| range.start < range.end);
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<i64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Части `range`, не покрытые `covered`. `covered` отсортированы и не пересекаются.
+fn subtract_ranges(range: Range<i64>, covered: &[Range<i64>]) -> Vec<Range<i64>> {
+    let mut missing = Vec::new();
+    let mut start = range.start;
+    for covered in covered {
+        if covered.end <= start {
+            continue;
+        }
+        if covered.start >= range.end {
+            break;
+        }
+        if covered.start > start {
+            missing.push(start..covered.start);
+        }
+        start = covered.end;
+    }
+    if start < range.end {
+        missing.push(start..range.end);
+    }
+    missing
+}
+
+/// Ошибка хранилища свечей.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CandleStoreError {
+    Sqlite(rusqlite::Error),
+    Api(TinkoffInvestError),
+}
+
+impl fmt::Display for CandleStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sqlite(error) => write!(f, "sqlite error: {}", error),
+            Self::Api(error) => write!(f, "api error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CandleStoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Sqlite(error) => Some(error),
+            Self::Api(error) => Some(error),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for CandleStoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Sqlite(error)
+    }
+}
+
+impl From<TinkoffInvestError> for CandleStoreError {
+    fn from(error: TinkoffInvestError) -> Self {
+        Self::Api(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_fixtures::at;
+
+    /// Свеча с дробной ценой закрытия, чтобы проверить хранение десятичных цен.
+    fn candle(time: DateTime<Utc>, close: i64) -> Candle {
+        Candle {
+            close: Decimal::new(close * 100 + 5, 2),
+            ..crate::test_fixtures::candle(time, close)
+        }
+    }
+
+    #[test]
+    fn stores_candles_and_tracks_synced_ranges() {
+        let uid: InstrumentUid = "e6123145-9665-43e0-8413-cd61b8aa9b13".parse().unwrap();
+        let interval = CandleInterval::OneMinute;
+        let mut store = CandleStore::open_in_memory().unwrap();
+        let candles = [candle(at(3, 7, 1), 3), candle(at(3, 7, 0), 2)];
+        store.insert(&uid, interval, &candles).unwrap();
+        store
+            .insert(&uid, interval, &[candle(at(3, 7, 1), 4)])
+            .unwrap();
+
+        let stored: Vec<Candle> = store
+            .candles(&uid, interval, at(3, 0, 0)..at(4, 0, 0))
+            .unwrap()
+            .collect();
+        assert_eq!(stored, vec![candle(at(3, 7, 0), 2), candle(at(3, 7, 1), 4)]);
+        assert_eq!(stored[1].close, Decimal::new(405, 2));
+
+        store
+            .mark_synced(&uid, interval, at(3, 0, 0)..at(3, 12, 0))
+            .unwrap();
+        store
+            .mark_synced(&uid, interval, at(3, 12, 0)..at(4, 0, 0))
+            .unwrap();
+        store
+            .mark_synced(&uid, interval, at(5, 0, 0)..at(6, 0, 0))
+            .unwrap();
+        assert_eq!(
+            store
+                .missing_ranges(&uid, interval, at(2, 0, 0)..at(7, 0, 0))
+                .unwrap(),
+            vec![
+                at(2, 0, 0)..at(3, 0, 0),
+                at(4, 0, 0)..at(5, 0, 0),
+                at(6, 0, 0)..at(7, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn resyncs_interval_that_was_current_during_sync() {
+        let uid: InstrumentUid = "e6123145-9665-43e0-8413-cd61b8aa9b13".parse().unwrap();
+        let interval = CandleInterval::OneMinute;
+        let mut store = CandleStore::open_in_memory().unwrap();
+        // Синхронизация в середине минуты, сделок за которую ещё не было
+        let end = synced_until(at(3, 7, 30) + Duration::seconds(30), interval);
+        assert_eq!(end, at(3, 7, 30));
+        store.mark_synced(&uid, interval, at(3, 7, 0)..end).unwrap();
+        assert_eq!(
+            store
+                .missing_ranges(&uid, interval, at(3, 7, 0)..at(3, 7, 31))
+                .unwrap(),
+            vec![at(3, 7, 30)..at(3, 7, 31)]
+        );
+
+        // Предыдущая свеча ещё может публиковаться
+        assert_eq!(
+            synced_until(at(3, 7, 30) + Duration::seconds(5), interval),
+            at(3, 7, 29)
+        );
+        assert_eq!(
+            synced_until(at(3, 18, 0), CandleInterval::Day),
+            at(2, 21, 0)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_stored_values() {
+        let store = CandleStore::open_in_memory().unwrap();
+        let read = |time: i64, open: &str| {
+            store.connection.query_row(
+                "SELECT ?1, ?2, '2', '1', '1.5', 10, 1",
+                params![time, open],
+                candle_from_row,
+            )
+        };
+        assert_eq!(
+            read(at(3, 7, 0).timestamp_millis(), "1").unwrap().time,
+            at(3, 7, 0)
+        );
+        assert!(matches!(
+            read(i64::MAX, "1"),
+            Err(rusqlite::Error::FromSqlConversionFailure(
+                0,
+                Type::Integer,
+                _
+            ))
+        ));
+        assert!(matches!(
+            read(at(3, 7, 0).timestamp_millis(), "x"),
+            Err(rusqlite::Error::FromSqlConversionFailure(1, Type::Text, _))
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+//! Проверка истории свечей по торговому календарю: пропуски внутри сессий, свечи вне сессий,
+//! свечи без объёма, противоречивые цены и повторы.
+//!
+//! ```no_run
+//! # fn run(
+//! #     candles: &[tinkoff_invest_sdk::types::Candle],
+//! #     schedule: &tinkoff_invest_sdk::types::TradingSchedule,
+//! #     range: std::ops::Range<tinkoff_invest_sdk::chrono::DateTime<tinkoff_invest_sdk::chrono::Utc>>,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! use tinkoff_invest_sdk::candle_validation::validate_candles;
+//! use tinkoff_invest_sdk::trading_calendar::TradingCalendar;
+//! use tinkoff_invest_sdk::types::CandleInterval;
+//!
+//! let calendar = TradingCalendar::from(schedule);
+//! validate_candles(candles, CandleInterval::OneMinute, range, &calendar).ensure_clean()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::trading_calendar::TradingCalendar;
+use crate::types::{moscow_day_start, Candle, CandleInterval, MoscowTime};
+
+/// Найденные проблемы. Время свечей — начало их интервала.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CandleReport {
+    /// Интервалы торговых сессий без свечей (для дневных свечей — торговые дни).
+    pub missing: Vec<Range<DateTime<Utc>>>,
+    /// Свечи вне торговых сессий. Свечи за даты, которых нет в календаре, не проверяются.
+    pub outside_sessions: Vec<DateTime<Utc>>,
+    /// Идущие подряд без пропусков свечи с нулевым объёмом: от начала первой до конца последней.
+    pub zero_volume_runs: Vec<Range<DateTime<Utc>>>,
+    /// Свечи, у которых high ниже open, close или low, или low выше open или close.
+    pub inconsistent: Vec<DateTime<Utc>>,
+    /// Время, которое встречается больше одного раза.
+    pub duplicates: Vec<DateTime<Utc>>,
+}
+
+impl CandleReport {
+    pub fn is_clean(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Ошибка, если найдена хоть одна проблема.
+    pub fn ensure_clean(self) -> Result<(), CandleReport> {
+        if self.is_clean() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for CandleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "candle history has {} missing ranges, {} candles outside sessions, \
+             {} zero volume runs, {} inconsistent candles, {} duplicates",
+            self.missing.len(),
+            self.outside_sessions.len(),
+            self.zero_volume_runs.len(),
+            self.inconsistent.len(),
+            self.duplicates.len()
+        )
+    }
+}
+
+impl std::error::Error for CandleReport {}
+
+/// Проверить свечи интервала `interval` за `range`. Порядок свечей не важен, свечи
+/// вне `range` не проверяются.
+///
+/// По малоликвидным инструментам свечей нет и в минуты без сделок, поэтому пропуски
+/// для них ожидаемы.
+pub fn validate_candles(
+    candles: &[Candle],
+    interval: CandleInterval,
+    range: Range<DateTime<Utc>>,
+    calendar: &TradingCalendar,
+) -> CandleReport {
+    let mut sorted: Vec<&Candle> = candles
+        .iter()
+        .filter(|candle| range.contains(&candle.time))
+        .collect();
+    sorted.sort_by_key(|candle| candle.time);
+    let times: Vec<DateTime<Utc>> = sorted.iter().map(|candle| candle.time).collect();
+    let step = interval.duration().unwrap_or_else(Duration::zero);
+
+    let mut report = CandleReport {
+        missing: find_gaps(&times, interval, range, calendar),
+        ..CandleReport::default()
+    };
+    for (index, candle) in sorted.iter().enumerate() {
+        if index > 0 && times[index - 1] == candle.time {
+            if report.duplicates.last() != Some(&candle.time) {
+                report.duplicates.push(candle.time);
+            }
+            continue;
+        }
+        if is_outside_sessions(candle.time, interval, calendar) {
+            report.outside_sessions.push(candle.time);
+        }
+        if !is_consistent(candle) {
+            report.inconsistent.push(candle.time);
+        }
+        if candle.volume == 0 {
+            let end = candle.time + step;
+            match report.zero_volume_runs.last_mut() {
+                Some(run)
+                    if index > 0
+                        && sorted[index - 1].volume == 0
+                        && sorted[index - 1].time + step == candle.time =>
+                {
+                    run.end = end
+                }
+                _ => report.zero_volume_runs.push(candle.time..end),
+            }
+        }
+    }
+    report
+}
+
+fn is_consistent(candle: &Candle) -> bool {
+    candle.low <= candle.high
+        && candle.open.max(candle.close) <= candle.high
+        && candle.open.min(candle.close) >= candle.low
+}
+
+fn is_outside_sessions(
+    time: DateTime<Utc>,
+    interval: CandleInterval,
+    calendar: &TradingCalendar,
+) -> bool {
+    if interval == CandleInterval::Day {
+        return calendar.is_trading_day(time.moscow_date()) == Some(false);
+    }
+    calendar.session_phase_at(time).is_some() && !calendar.is_open_at(time)
+}
+
+/// Интервалы без свечей внутри торговых сессий. `times` отсортированы.
+pub(crate) fn find_gaps(
+    times: &[DateTime<Utc>],
+    interval: CandleInterval,
+    range: Range<DateTime<Utc>>,
+    calendar: &TradingCalendar,
+) -> Vec<Range<DateTime<Utc>>> {
+    let step = match interval.duration() {
+        Some(step) => step,
+        None => return Vec::new(),
+    };
+    let has_candle = |slot: &Range<DateTime<Utc>>| {
+        let index = times.partition_point(|time| *time < slot.start);
+        matches!(times.get(index), Some(time) if *time < slot.end)
+    };
+    let mut slots = Vec::new();
+    if interval == CandleInterval::Day {
+        let dates =
+            range.start.moscow_date()..=(range.end - Duration::nanoseconds(1)).moscow_date();
+        for date in calendar.trading_days(dates) {
+            let start = moscow_day_start(date);
+            slots.push(start.max(range.start)..(start + step).min(range.end));
+        }
+    } else {
+        let epoch = DateTime::<Utc>::from(UNIX_EPOCH);
+        let step_millis = step.num_milliseconds();
+        for session in calendar.sessions() {
+            let start = session.start.max(range.start);
+            let end = session.end.min(range.end);
+            if start >= end {
+                continue;
+            }
+            // Свечи выровнены по началу интервала от эпохи Unix.
+            let offset = (start - epoch).num_milliseconds();
+            let mut slot = epoch + Duration::milliseconds(offset - offset.rem_euclid(step_millis));
+            while slot < end {
+                slots.push(slot.max(start)..(slot + step).min(end));
+                slot += step;
+            }
+        }
+    }
+    let mut gaps: Vec<Range<DateTime<Utc>>> = Vec::new();
+    for slot in slots.into_iter().filter(|slot| !has_candle(slot)) {
+        match gaps.last_mut() {
+            Some(last) if last.end == slot.start => last.end = slot.end,
+            _ => gaps.push(slot),
+        }
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+    use crate::test_fixtures::{at, calendar, trading_day};
+
+    fn short_session() -> TradingCalendar {
+        calendar([trading_day(3, (7, 0), (7, 10))])
+    }
+
+    fn candle(time: DateTime<Utc>) -> Candle {
+        crate::test_fixtures::candle(time, 10)
+    }
+
+    #[test]
+    fn finds_gaps_inside_sessions() {
+        let calendar = short_session();
+        let times = [at(3, 7, 0), at(3, 7, 1), at(3, 7, 5), at(3, 7, 9)];
+        let gaps = find_gaps(
+            &times,
+            CandleInterval::OneMinute,
+            at(3, 0, 0)..at(4, 0, 0),
+            &calendar,
+        );
+        assert_eq!(
+            gaps,
+            vec![at(3, 7, 2)..at(3, 7, 5), at(3, 7, 6)..at(3, 7, 9)]
+        );
+        assert_eq!(
+            find_gaps(
+                &[],
+                CandleInterval::Day,
+                at(3, 0, 0)..at(4, 0, 0),
+                &calendar
+            ),
+            vec![at(3, 0, 0)..at(3, 21, 0)]
+        );
+    }
+
+    #[test]
+    fn reports_bad_candles() {
+        let mut candles: Vec<Candle> = (0..10).map(|minute| candle(at(3, 7, minute))).collect();
+        candles[2].volume = 0;
+        candles[3].volume = 0;
+        candles[4].high = Decimal::from(8);
+        candles.push(candle(at(3, 7, 6)));
+        candles.push(candle(at(3, 12, 0)));
+        // Вне проверяемого интервала
+        candles.push(candle(at(3, 14, 0)));
+        candles.remove(8);
+
+        let report = validate_candles(
+            &candles,
+            CandleInterval::OneMinute,
+            at(3, 7, 0)..at(3, 13, 0),
+            &short_session(),
+        );
+        assert_eq!(report.missing, vec![at(3, 7, 8)..at(3, 7, 9)]);
+        assert_eq!(report.outside_sessions, vec![at(3, 12, 0)]);
+        assert_eq!(report.zero_volume_runs, vec![at(3, 7, 2)..at(3, 7, 4)]);
+        assert_eq!(report.inconsistent, vec![at(3, 7, 4)]);
+        assert_eq!(report.duplicates, vec![at(3, 7, 6)]);
+        assert!(report.ensure_clean().is_err());
+
+        let clean: Vec<Candle> = (0..10).map(|minute| candle(at(3, 7, minute))).collect();
+        let range = at(3, 7, 0)..at(3, 7, 10);
+        assert!(validate_candles(
+            &clean,
+            CandleInterval::OneMinute,
+            range.clone(),
+            &short_session()
+        )
+        .is_clean());
+
+        // Свечи без объёма по разные стороны пропуска — разные серии
+        let mut split: Vec<Candle> = clean
+            .into_iter()
+            .filter(|candle| candle.time != at(3, 7, 5))
+            .collect();
+        split[4].volume = 0;
+        split[5].volume = 0;
+        let report = validate_candles(&split, CandleInterval::OneMinute, range, &short_session());
+        assert_eq!(
+            report.zero_volume_runs,
+            vec![at(3, 7, 4)..at(3, 7, 5), at(3, 7, 6)..at(3, 7, 7)]
+        );
+    }
+}
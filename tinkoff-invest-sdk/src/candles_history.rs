@@ -0,0 +1,332 @@
+//! Загрузка истории свечей за произвольный интервал.
+//!
+//! API отдаёт свечи за ограниченный интервал (см. [`CandleInterval::max_request_span`]), поэтому
+//! интервал разбивается на части, которые запрашиваются по очереди с учётом лимита запросов.
+//!
+//! ```no_run
+//! # async fn run(client: tinkoff_invest_sdk::TinkoffInvestClient) -> Result<(), Box<dyn std::error::Error>> {
+//! use futures::TryStreamExt;
+//! use tinkoff_invest_sdk::chrono::{Duration, Utc};
+//! use tinkoff_invest_sdk::types::{CandleInterval, Figi};
+//!
+//! let figi: Figi = "BBG004730N88".parse()?;
+//! let to = Utc::now();
+//! let from = to - Duration::days(30);
+//! let calendar = client
+//!     .instruments()
+//!     .trading_calendar("MOEX".to_owned(), from..to)
+//!     .await?
+//!     .ok_or("no MOEX trading schedule")?;
+//! let candles: Vec<_> = client
+//!     .market_data()
+//!     .candles_history(figi, CandleInterval::OneMinute, from..to)
+//!     .trading_calendar(calendar)
+//!     .try_collect()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+use tinkoff_invest_grpc::api;
+use tokio::time::{sleep, Instant};
+
+use crate::error::ErrorType;
+use crate::market_data::MarketDataClient;
+use crate::shared::date_range_to_timestamp_pair;
+use crate::trading_calendar::TradingCalendar;
+use crate::types::{moscow_day_start, Candle, CandleInterval, InstrumentId, MoscowTime};
+
+const DEFAULT_REQUESTS_PER_MINUTE: u32 = 300;
+const DEFAULT_MAX_RETRIES: u32 = 5;
+const INITIAL_BACKOFF: StdDuration = StdDuration::from_secs(1);
+
+/// Поток свечей за интервал по возрастанию времени, см. [`MarketDataClient::candles_history`].
+///
+/// Настраивается до первого опроса потока.
+pub struct CandlesHistory {
+    config: Option<Config>,
+    stream: Option<BoxStream<'static, crate::Result<Candle>>>,
+}
+
+struct Config {
+    client: MarketDataClient,
+    instrument: InstrumentId,
+    interval: CandleInterval,
+    range: Range<DateTime<Utc>>,
+    calendar: Option<TradingCalendar>,
+    resume_after: Option<DateTime<Utc>>,
+    requests_per_minute: u32,
+    max_retries: u32,
+}
+
+impl CandlesHistory {
+    pub(crate) fn new(
+        client: MarketDataClient,
+        instrument: InstrumentId,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            config: Some(Config {
+                client,
+                instrument,
+                interval,
+                range,
+                calendar: None,
+                resume_after: None,
+                requests_per_minute: DEFAULT_REQUESTS_PER_MINUTE,
+                max_retries: DEFAULT_MAX_RETRIES,
+            }),
+            stream: None,
+        }
+    }
+
+    /// Не запрашивать свечи за дни, которые по календарю неторговые.
+    /// Дни, которых нет в календаре, запрашиваются.
+    pub fn trading_calendar(mut self, calendar: TradingCalendar) -> Self {
+        self.config_mut().calendar = Some(calendar);
+        self
+    }
+
+    /// Продолжить прерванную загрузку: вернуть только свечи, которые начинаются позже `time`.
+    pub fn resume_after(mut self, time: DateTime<Utc>) -> Self {
+        self.config_mut().resume_after = Some(time);
+        self
+    }
+
+    /// Не больше `requests_per_minute` запросов в минуту. По умолчанию 300.
+    pub fn rate_limit(mut self, requests_per_minute: u32) -> Self {
+        self.config_mut().requests_per_minute = requests_per_minute.max(1);
+        self
+    }
+
+    /// Сколько раз повторять запрос, если превышен лимит запросов. По умолчанию 5.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.config_mut().max_retries = max_retries;
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut Config {
+        self.config
+            .as_mut()
+            .expect("CandlesHistory is configured after polling")
+    }
+}
+
+impl Stream for CandlesHistory {
+    type Item = crate::Result<Candle>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.stream.is_none() {
+            let config = self.config.take().expect("CandlesHistory config");
+            self.stream = Some(config.into_stream());
+        }
+        self.stream
+            .as_mut()
+            .expect("CandlesHistory stream")
+            .poll_next_unpin(cx)
+    }
+}
+
+struct State {
+    client: MarketDataClient,
+    request: api::GetCandlesRequest,
+    chunks: VecDeque<Range<DateTime<Utc>>>,
+    candles: VecDeque<Candle>,
+    last_time: Option<DateTime<Utc>>,
+    min_delay: StdDuration,
+    last_request: Option<Instant>,
+    max_retries: u32,
+    failed: bool,
+}
+
+impl Config {
+    fn into_stream(self) -> BoxStream<'static, crate::Result<Candle>> {
+        let start = match self.resume_after {
+            Some(time) => self.range.start.max(time),
+            None => self.range.start,
+        };
+        let chunks = plan_chunks(
+            start..self.range.end,
+            self.interval.max_request_span(),
+            self.calendar.as_ref(),
+        );
+        let state = State {
+            client: self.client,
+            request: api::GetCandlesRequest {
+                instrument_id: self.instrument.to_string(),
+                interval: self.interval.into(),
+                ..Default::default()
+            },
+            chunks: chunks.into(),
+            candles: VecDeque::new(),
+            last_time: self.resume_after,
+            min_delay: StdDuration::from_secs(60) / self.requests_per_minute,
+            last_request: None,
+            max_retries: self.max_retries,
+            failed: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            let item = state.next_candle().await?;
+            Some((item, state))
+        })
+        .boxed()
+    }
+}
+
+impl State {
+    async fn next_candle(&mut self) -> Option<crate::Result<Candle>> {
+        loop {
+            if let Some(candle) = self.candles.pop_front() {
+                return Some(Ok(candle));
+            }
+            if self.failed {
+                return None;
+            }
+            let chunk = self.chunks.pop_front()?;
+            match self.fetch(chunk).await {
+                Ok(candles) => {
+                    self.candles = dedup_after(candles, &mut self.last_time).into();
+                }
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+
+    async fn fetch(&mut self, chunk: Range<DateTime<Utc>>) -> crate::Result<Vec<Candle>> {
+        let (from, to) = date_range_to_timestamp_pair(chunk);
+        let request = api::GetCandlesRequest {
+            from,
+            to,
+            ..self.request.clone()
+        };
+        let mut backoff = INITIAL_BACKOFF;
+        let mut retries = 0;
+        loop {
+            if let Some(last_request) = self.last_request {
+                sleep(self.min_delay.saturating_sub(last_request.elapsed())).await;
+            }
+            self.last_request = Some(Instant::now());
+            match self.client.get_candles_raw(request.clone()).await {
+                Ok(response) => {
+                    return Ok(response
+                        .candles
+                        .iter()
+                        .filter_map(Candle::from_api)
+                        .collect())
+                }
+                Err(error)
+                    if error.error_type() == &ErrorType::ResourceExhausted
+                        && retries < self.max_retries =>
+                {
+                    retries += 1;
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Части интервала, каждая не длиннее `span`. Границы частей выровнены по полуночи по Москве,
+/// чтобы части совпадали с торговыми днями. Части, все дни которых неторговые, пропускаются.
+fn plan_chunks(
+    range: Range<DateTime<Utc>>,
+    span: Option<Duration>,
+    calendar: Option<&TradingCalendar>,
+) -> Vec<Range<DateTime<Utc>>> {
+    let mut chunks = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let end = match span {
+            Some(span) => (moscow_day_start(start.moscow_date()) + span).min(range.end),
+            None => range.end,
+        };
+        let chunk = start..end;
+        match calendar {
+            Some(calendar) if is_non_trading(calendar, &chunk) => {}
+            _ => chunks.push(chunk),
+        }
+        start = end;
+    }
+    chunks
+}
+
+/// Все дни части интервала есть в календаре, и ни один из них не торговый.
+fn is_non_trading(calendar: &TradingCalendar, chunk: &Range<DateTime<Utc>>) -> bool {
+    let first = chunk.start.moscow_date();
+    let last = (chunk.end - Duration::nanoseconds(1)).moscow_date();
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .all(|date| calendar.is_trading_day(date) == Some(false))
+}
+
+/// Свечи по возрастанию времени, начинающиеся позже `last_time`: свеча на границе двух частей
+/// может прийти в обоих ответах.
+fn dedup_after(mut candles: Vec<Candle>, last_time: &mut Option<DateTime<Utc>>) -> Vec<Candle> {
+    candles.sort_by_key(|candle| candle.time);
+    candles.retain(|candle| {
+        let is_new = match *last_time {
+            Some(last) => candle.time > last,
+            None => true,
+        };
+        if is_new {
+            *last_time = Some(candle.time);
+        }
+        is_new
+    });
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_fixtures::{at, calendar, candle, non_trading_day, trading_day};
+
+    #[test]
+    fn splits_by_moscow_days_and_skips_weekends() {
+        let calendar = calendar([
+            trading_day(3, (7, 0), (15, 40)),
+            non_trading_day(4),
+            non_trading_day(5),
+            trading_day(6, (7, 0), (15, 40)),
+        ]);
+        let chunks = plan_chunks(
+            at(3, 12, 0)..at(6, 12, 0),
+            CandleInterval::OneMinute.max_request_span(),
+            Some(&calendar),
+        );
+        assert_eq!(
+            chunks,
+            vec![at(3, 12, 0)..at(3, 21, 0), at(5, 21, 0)..at(6, 12, 0)]
+        );
+
+        let chunks = plan_chunks(at(3, 12, 0)..at(6, 12, 0), None, None);
+        assert_eq!(chunks, vec![at(3, 12, 0)..at(6, 12, 0)]);
+    }
+
+    #[test]
+    fn drops_boundary_duplicates() {
+        let candle = |hour| candle(at(3, hour, 0), 1);
+        let mut last_time = Some(at(3, 10, 0));
+        let candles = dedup_after(vec![candle(12), candle(10), candle(11)], &mut last_time);
+        assert_eq!(candles, vec![candle(11), candle(12)]);
+        assert_eq!(last_time, Some(at(3, 12, 0)));
+        assert!(dedup_after(vec![candle(12)], &mut last_time).is_empty());
+    }
+}
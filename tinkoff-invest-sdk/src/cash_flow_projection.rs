@@ -0,0 +1,546 @@
+//! Прогноз денежных потоков по портфелю облигаций: купоны, погашения и календарь выплат.
+//!
+//! ```no_run
+//! # async fn run(client: tinkoff_invest_sdk::TinkoffInvestClient) -> Result<(), Box<dyn std::error::Error>> {
+//! use tinkoff_invest_sdk::cash_flow_projection::{BondHolding, CashFlowProjector};
+//! use tinkoff_invest_sdk::chrono::NaiveDate;
+//!
+//! let holdings = [BondHolding::new("BBG00R05JT04".parse()?, 10)];
+//! let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+//! let to = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+//! let schedule = CashFlowProjector::new(client.instruments())
+//!     .project(&holdings, from..=to)
+//!     .await?;
+//! schedule.write_ics(std::fs::File::create("coupons.ics")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::bond_analytics::{
+    cash_flows_between, BondAnalyticsError, CashFlowKind, EstimationPolicy,
+};
+use crate::error::TinkoffInvestError;
+use crate::instruments::{InstrumentRequest, InstrumentsClient};
+use crate::types::{Bond, Coupon, Figi};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Позиция по облигации.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BondHolding {
+    pub figi: Figi,
+    /// Количество облигаций (не лотов).
+    pub quantity: u64,
+}
+
+impl BondHolding {
+    pub fn new(figi: Figi, quantity: u64) -> Self {
+        Self { figi, quantity }
+    }
+}
+
+/// Выплата по позиции.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectedCashFlow {
+    pub figi: Figi,
+    pub name: String,
+    pub currency: String,
+    pub date: NaiveDate,
+    pub kind: CashFlowKind,
+    /// Сумма на всю позицию.
+    pub amount: Decimal,
+    /// Сумма не известна заранее и была оценена.
+    pub estimated: bool,
+}
+
+/// Сумма выплат за месяц в одной валюте.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthlyCashFlow {
+    pub year: i32,
+    pub month: u32,
+    pub currency: String,
+    pub amount: Decimal,
+}
+
+/// График выплат, упорядоченный по дате.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CashFlowSchedule {
+    flows: Vec<ProjectedCashFlow>,
+    amortizing: Vec<Figi>,
+}
+
+impl CashFlowSchedule {
+    pub fn new(mut flows: Vec<ProjectedCashFlow>) -> Self {
+        flows.sort_by(|a, b| (a.date, &a.figi).cmp(&(b.date, &b.figi)));
+        Self {
+            flows,
+            amortizing: Vec::new(),
+        }
+    }
+
+    pub fn flows(&self) -> &[ProjectedCashFlow] {
+        &self.flows
+    }
+
+    /// Облигации с амортизацией. API не отдаёт график погашения номинала,
+    /// поэтому по ним в графике только купоны, без возврата номинала.
+    pub fn amortizing(&self) -> &[Figi] {
+        &self.amortizing
+    }
+
+    /// Суммы выплат по месяцам и валютам.
+    pub fn monthly_totals(&self) -> Vec<MonthlyCashFlow> {
+        let mut totals: BTreeMap<(i32, u32, &str), Decimal> = BTreeMap::new();
+        for flow in &self.flows {
+            let key = (flow.date.year(), flow.date.month(), flow.currency.as_str());
+            *totals.entry(key).or_default() += flow.amount;
+        }
+        totals
+            .into_iter()
+            .map(|((year, month, currency), amount)| MonthlyCashFlow {
+                year,
+                month,
+                currency: currency.to_owned(),
+                amount,
+            })
+            .collect()
+    }
+
+    /// Суммы всех выплат по валютам.
+    pub fn totals_by_currency(&self) -> BTreeMap<String, Decimal> {
+        let mut totals = BTreeMap::new();
+        for flow in &self.flows {
+            *totals.entry(flow.currency.clone()).or_default() += flow.amount;
+        }
+        totals
+    }
+
+    /// Выгрузить выплаты в CSV.
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "date,figi,name,kind,currency,amount,estimated")?;
+        for flow in &self.flows {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                flow.date,
+                csv_field(flow.figi.as_str()),
+                csv_field(&flow.name),
+                kind_name(flow.kind),
+                csv_field(&flow.currency),
+                flow.amount,
+                flow.estimated
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Выгрузить выплаты в iCalendar (RFC 5545): по событию на весь день для каждой выплаты.
+    pub fn write_ics(&self, mut writer: impl Write) -> io::Result<()> {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        write_ics_line(&mut writer, "BEGIN:VCALENDAR")?;
+        write_ics_line(&mut writer, "VERSION:2.0")?;
+        write_ics_line(&mut writer, "PRODID:-//tinkoff-invest-sdk//cash flows//RU")?;
+        for flow in &self.flows {
+            let date = flow.date.format("%Y%m%d");
+            let title = match flow.kind {
+                CashFlowKind::Coupon => "Купон",
+                CashFlowKind::Redemption => "Погашение",
+            };
+            let estimate = if flow.estimated {
+                " (оценка)"
+            } else {
+                ""
+            };
+            write_ics_line(&mut writer, "BEGIN:VEVENT")?;
+            write_ics_line(
+                &mut writer,
+                &format!(
+                    "UID:{}-{}-{}@tinkoff-invest-sdk",
+                    flow.figi,
+                    date,
+                    kind_name(flow.kind)
+                ),
+            )?;
+            write_ics_line(&mut writer, &format!("DTSTAMP:{stamp}"))?;
+            write_ics_line(&mut writer, &format!("DTSTART;VALUE=DATE:{date}"))?;
+            write_ics_line(
+                &mut writer,
+                &format!(
+                    "SUMMARY:{}",
+                    ics_text(&format!(
+                        "{title}: {} — {} {}{estimate}",
+                        flow.name, flow.amount, flow.currency
+                    ))
+                ),
+            )?;
+            write_ics_line(&mut writer, "END:VEVENT")?;
+        }
+        write_ics_line(&mut writer, "END:VCALENDAR")
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProjectionError {
+    Api(TinkoffInvestError),
+    /// Облигация с таким FIGI не найдена.
+    BondNotFound(Figi),
+    /// Не удалось построить выплаты по облигации.
+    Analytics {
+        figi: Figi,
+        error: BondAnalyticsError,
+    },
+}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(error) => write!(f, "{error}"),
+            Self::BondNotFound(figi) => write!(f, "bond {figi} not found"),
+            Self::Analytics { figi, error } => write!(f, "bond {figi}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api(error) => Some(error),
+            Self::BondNotFound(_) => None,
+            Self::Analytics { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<TinkoffInvestError> for ProjectionError {
+    fn from(error: TinkoffInvestError) -> Self {
+        Self::Api(error)
+    }
+}
+
+/// Строит график выплат по набору позиций, запрашивая данные по облигациям параллельно.
+pub struct CashFlowProjector {
+    client: InstrumentsClient,
+    concurrency: usize,
+    policy: EstimationPolicy,
+}
+
+impl CashFlowProjector {
+    pub fn new(client: InstrumentsClient) -> Self {
+        Self {
+            client,
+            concurrency: DEFAULT_CONCURRENCY,
+            policy: EstimationPolicy::Estimate,
+        }
+    }
+
+    /// Максимальное число облигаций, данные по которым запрашиваются одновременно.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Что делать с неизвестными купонами и амортизацией.
+    /// По умолчанию купоны оцениваются, а у облигаций с амортизацией пропускается
+    /// возврат номинала, см. [`CashFlowSchedule::amortizing`].
+    /// С [`EstimationPolicy::Strict`] такие облигации приводят к ошибке.
+    pub fn estimation_policy(mut self, policy: EstimationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Выплаты по позициям с датами из интервала. Позиции с одинаковым FIGI, например
+    /// с разных счетов, складываются.
+    pub async fn project(
+        &self,
+        holdings: &[BondHolding],
+        dates: RangeInclusive<NaiveDate>,
+    ) -> Result<CashFlowSchedule, ProjectionError> {
+        let holdings = merge_holdings(holdings);
+        let projections: Vec<HoldingProjection> = stream::iter(&holdings)
+            .map(|holding| self.project_holding(holding, dates.clone()))
+            .buffer_unordered(self.concurrency)
+            .try_collect()
+            .await?;
+        let mut amortizing = Vec::new();
+        let mut flows = Vec::new();
+        for projection in projections {
+            if projection.amortizing {
+                amortizing.push(projection.figi);
+            }
+            flows.extend(projection.flows);
+        }
+        amortizing.sort();
+        let mut schedule = CashFlowSchedule::new(flows);
+        schedule.amortizing = amortizing;
+        Ok(schedule)
+    }
+
+    async fn project_holding(
+        &self,
+        holding: &BondHolding,
+        dates: RangeInclusive<NaiveDate>,
+    ) -> Result<HoldingProjection, ProjectionError> {
+        let mut client = self.client.clone();
+        let figi = &holding.figi;
+        let bond = client
+            .bond_by(InstrumentRequest::Figi(figi.clone()))
+            .await?
+            .ok_or_else(|| ProjectionError::BondNotFound(figi.clone()))?;
+        // Прошлые купоны тоже нужны: по ним оцениваются ещё не объявленные
+        let coupons = client.get_bond_coupons(figi, ..=*dates.end()).await?;
+        project_flows(holding, &bond, &coupons, dates, self.policy)
+    }
+}
+
+/// Сложить позиции по одному FIGI, чтобы у каждой выплаты в графике была одна строка
+/// и уникальный UID в iCalendar.
+fn merge_holdings(holdings: &[BondHolding]) -> Vec<BondHolding> {
+    let mut merged: Vec<BondHolding> = Vec::with_capacity(holdings.len());
+    for holding in holdings {
+        match merged.iter_mut().find(|merged| merged.figi == holding.figi) {
+            Some(merged) => merged.quantity += holding.quantity,
+            None => merged.push(holding.clone()),
+        }
+    }
+    merged
+}
+
+/// Выплаты по одной позиции.
+#[derive(Debug)]
+struct HoldingProjection {
+    figi: Figi,
+    flows: Vec<ProjectedCashFlow>,
+    amortizing: bool,
+}
+
+fn project_flows(
+    holding: &BondHolding,
+    bond: &Bond,
+    coupons: &[Coupon],
+    dates: RangeInclusive<NaiveDate>,
+    policy: EstimationPolicy,
+) -> Result<HoldingProjection, ProjectionError> {
+    let figi = &holding.figi;
+    let flows = cash_flows_between(bond, coupons, dates, policy).map_err(|error| {
+        ProjectionError::Analytics {
+            figi: figi.clone(),
+            error,
+        }
+    })?;
+
+    let amortizing = bond.with_amortization();
+    let currency = bond
+        .nominal()
+        .map(|nominal| nominal.currency().to_owned())
+        .unwrap_or_else(|| bond.currency().to_owned());
+    let quantity = Decimal::from(holding.quantity);
+    let flows = flows
+        .into_iter()
+        // Весь остаток номинала в дату погашения — неверная оценка для амортизации
+        .filter(|flow| !(amortizing && flow.kind == CashFlowKind::Redemption))
+        .map(|flow| ProjectedCashFlow {
+            figi: figi.clone(),
+            name: bond.name().to_owned(),
+            currency: currency.clone(),
+            date: flow.date,
+            kind: flow.kind,
+            amount: flow.amount * quantity,
+            estimated: flow.estimated,
+        })
+        .collect();
+    Ok(HoldingProjection {
+        figi: figi.clone(),
+        flows,
+        amortizing,
+    })
+}
+
+fn kind_name(kind: CashFlowKind) -> &'static str {
+    match kind {
+        CashFlowKind::Coupon => "coupon",
+        CashFlowKind::Redemption => "redemption",
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Записать строку iCalendar, перенося её по 75 байт, как требует RFC 5545.
+fn write_ics_line(writer: &mut impl Write, line: &str) -> io::Result<()> {
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            writer.write_all(b"\r\n ")?;
+            width = 1;
+        }
+        write!(writer, "{ch}")?;
+        width += ch.len_utf8();
+    }
+    writer.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(figi: &str, date: (i32, u32, u32), currency: &str, amount: i64) -> ProjectedCashFlow {
+        ProjectedCashFlow {
+            figi: figi.parse().unwrap(),
+            name: format!("Облигация {figi}, выпуск 1"),
+            currency: currency.to_owned(),
+            date: NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            kind: CashFlowKind::Coupon,
+            amount: Decimal::from(amount),
+            estimated: false,
+        }
+    }
+
+    fn schedule() -> CashFlowSchedule {
+        CashFlowSchedule::new(vec![
+            flow("BBG00R05JT04", (2023, 2, 10), "rub", 300),
+            flow("BBG004730N88", (2023, 1, 20), "rub", 100),
+            flow("BBG004731354", (2023, 1, 5), "usd", 7),
+            flow("BBG004730N88", (2023, 1, 25), "rub", 50),
+        ])
+    }
+
+    #[test]
+    fn aggregates_by_month_and_currency() {
+        let totals = schedule().monthly_totals();
+        let totals: Vec<_> = totals
+            .iter()
+            .map(|t| (t.year, t.month, t.currency.as_str(), t.amount))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                (2023, 1, "rub", Decimal::from(150)),
+                (2023, 1, "usd", Decimal::from(7)),
+                (2023, 2, "rub", Decimal::from(300)),
+            ]
+        );
+    }
+
+    #[test]
+    fn exports_csv_sorted_by_date() {
+        let mut csv = Vec::new();
+        schedule().write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], "date,figi,name,kind,currency,amount,estimated");
+        assert_eq!(
+            lines[1],
+            "2023-01-05,BBG004731354,\"Облигация BBG004731354, выпуск 1\",coupon,usd,7,false"
+        );
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn skips_principal_of_amortizing_bonds() {
+        use crate::types::chrono_timestamp_to_grpc_timestamp;
+        use chrono::TimeZone;
+        use tinkoff_invest_grpc::api;
+
+        let timestamp = |year| {
+            Some(chrono_timestamp_to_grpc_timestamp(
+                Utc.with_ymd_and_hms(year, 6, 1, 0, 0, 0).unwrap(),
+            ))
+        };
+        let rub = |units| api::MoneyValue {
+            currency: "rub".to_owned(),
+            units,
+            nano: 0,
+        };
+        let bond = |amortization_flag| -> Bond {
+            api::Bond {
+                nominal: Some(rub(1000)),
+                maturity_date: timestamp(2024),
+                amortization_flag,
+                ..Default::default()
+            }
+            .into()
+        };
+        let coupons: Vec<Coupon> = vec![api::Coupon {
+            coupon_number: 1,
+            coupon_date: timestamp(2024),
+            pay_one_bond: Some(rub(50)),
+            coupon_type: api::CouponType::Constant as i32,
+            ..Default::default()
+        }
+        .into()];
+        let holding = BondHolding::new("BBG00R05JT04".parse().unwrap(), 2);
+        let dates = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+            ..=NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let project =
+            |bond: &Bond, policy| project_flows(&holding, bond, &coupons, dates.clone(), policy);
+
+        let plain = project(&bond(false), EstimationPolicy::Estimate).unwrap();
+        assert!(!plain.amortizing);
+        assert_eq!(plain.flows.len(), 2);
+        assert_eq!(plain.flows[1].kind, CashFlowKind::Redemption);
+        assert_eq!(plain.flows[1].amount, Decimal::from(2000));
+
+        let amortizing = project(&bond(true), EstimationPolicy::Estimate).unwrap();
+        assert!(amortizing.amortizing);
+        let kinds: Vec<_> = amortizing.flows.iter().map(|flow| flow.kind).collect();
+        assert_eq!(kinds, vec![CashFlowKind::Coupon]);
+        assert_eq!(amortizing.flows[0].amount, Decimal::from(100));
+
+        assert!(matches!(
+            project(&bond(true), EstimationPolicy::Strict),
+            Err(ProjectionError::Analytics {
+                error: BondAnalyticsError::Amortization,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn merges_holdings_of_the_same_bond() {
+        let figi = |figi: &str| -> Figi { figi.parse().unwrap() };
+        let merged = merge_holdings(&[
+            BondHolding::new(figi("BBG00R05JT04"), 10),
+            BondHolding::new(figi("BBG004730N88"), 1),
+            BondHolding::new(figi("BBG00R05JT04"), 5),
+        ]);
+        assert_eq!(
+            merged,
+            vec![
+                BondHolding::new(figi("BBG00R05JT04"), 15),
+                BondHolding::new(figi("BBG004730N88"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_long_ics_lines() {
+        let mut ics = Vec::new();
+        schedule().write_ics(&mut ics).unwrap();
+        let ics = String::from_utf8(ics).unwrap();
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 4);
+    }
+}
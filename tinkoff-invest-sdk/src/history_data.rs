@@ -0,0 +1,315 @@
+//! Архивы минутных свечей (`history-data`).
+//!
+//! Брокер отдаёт минутные свечи инструмента за год одним zip-архивом: по CSV-файлу на торговый
+//! день. Для загрузки истории это намного дешевле, чем запросы свечей через API.
+//!
+//! Строка CSV: `instrument_uid;время UTC;open;close;high;low;volume;`.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+use zip::ZipArchive;
+
+#[cfg(feature = "history-data-download")]
+pub use download::HistoryDataClient;
+
+use crate::types::Candle;
+
+/// Свечи из одного CSV-файла в порядке строк.
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<Candle>, HistoryDataError> {
+    read_csv_named(reader, "")
+}
+
+/// Свечи из всех CSV-файлов zip-архива по возрастанию времени.
+pub fn read_zip<R: Read + Seek>(reader: R) -> Result<Vec<Candle>, HistoryDataError> {
+    let mut archive = ZipArchive::new(reader)?;
+    let mut candles = Vec::new();
+    for index in 0..archive.len() {
+        let file = archive.by_index(index)?;
+        if !file.is_file() || !file.name().ends_with(".csv") {
+            continue;
+        }
+        let name = file.name().to_owned();
+        candles.extend(read_csv_named(file, &name)?);
+    }
+    candles.sort_by_key(|candle| candle.time);
+    candles.dedup_by_key(|candle| candle.time);
+    Ok(candles)
+}
+
+/// Свечи из zip-архива на диске, см. [`read_zip`].
+pub fn read_zip_file(path: impl AsRef<Path>) -> Result<Vec<Candle>, HistoryDataError> {
+    read_zip(BufReader::new(File::open(path)?))
+}
+
+fn read_csv_named<R: Read>(reader: R, file: &str) -> Result<Vec<Candle>, HistoryDataError> {
+    let mut candles = Vec::new();
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let candle = parse_line(&line).map_err(|message| HistoryDataError::Parse {
+            file: file.to_owned(),
+            line: index + 1,
+            message,
+        })?;
+        candles.push(candle);
+    }
+    Ok(candles)
+}
+
+fn parse_line(line: &str) -> Result<Candle, String> {
+    let fields: Vec<&str> = line.trim_end().trim_end_matches(';').split(';').collect();
+    if fields.len() != 7 {
+        return Err(format!("expected 7 fields, got {}", fields.len()));
+    }
+    let price = |index: usize, name: &str| {
+        Decimal::from_str(fields[index]).map_err(|error| format!("{}: {}", name, error))
+    };
+    let time = DateTime::parse_from_rfc3339(fields[1])
+        .map_err(|error| format!("time: {}", error))?
+        .with_timezone(&Utc);
+    Ok(Candle {
+        open: price(2, "open")?,
+        close: price(3, "close")?,
+        high: price(4, "high")?,
+        low: price(5, "low")?,
+        volume: fields[6]
+            .parse()
+            .map_err(|error| format!("volume: {}", error))?,
+        time,
+        is_complete: true,
+    })
+}
+
+/// Ошибка чтения или загрузки архива свечей.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HistoryDataError {
+    Io(io::Error),
+    Zip(zip::result::ZipError),
+    /// Строка CSV не разобрана. `line` считается с единицы.
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    #[cfg(feature = "history-data-download")]
+    Http(reqwest::Error),
+    /// Сервер ответил кодом, отличным от 200 и 404.
+    #[cfg(feature = "history-data-download")]
+    Status(u16),
+}
+
+impl fmt::Display for HistoryDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::Zip(error) => write!(f, "zip error: {}", error),
+            Self::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "invalid csv at {}:{}: {}", file, line, message),
+            #[cfg(feature = "history-data-download")]
+            Self::Http(error) => write!(f, "http error: {}", error),
+            #[cfg(feature = "history-data-download")]
+            Self::Status(status) => write!(f, "unexpected http status {}", status),
+        }
+    }
+}
+
+impl std::error::Error for HistoryDataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Zip(error) => Some(error),
+            #[cfg(feature = "history-data-download")]
+            Self::Http(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HistoryDataError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<zip::result::ZipError> for HistoryDataError {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::Zip(error)
+    }
+}
+
+#[cfg(feature = "history-data-download")]
+mod download {
+    use std::io::Cursor;
+
+    use super::{read_zip, HistoryDataError};
+    use crate::types::{Candle, InstrumentId};
+
+    const DEFAULT_BASE_URL: &str = "https://invest-public-api.tinkoff.ru";
+
+    /// Загрузка архивов свечей по HTTP.
+    #[derive(Clone)]
+    pub struct HistoryDataClient {
+        http: reqwest::Client,
+        base_url: String,
+        token: String,
+    }
+
+    impl HistoryDataClient {
+        pub fn new(token: impl Into<String>) -> Self {
+            Self {
+                http: reqwest::Client::new(),
+                base_url: DEFAULT_BASE_URL.to_owned(),
+                token: token.into(),
+            }
+        }
+
+        /// Адрес сервера без завершающего `/`, например для тестового сервера.
+        pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+            self.base_url = base_url.into().trim_end_matches('/').to_owned();
+            self
+        }
+
+        /// Zip-архив свечей инструмента за год. `None`, если за этот год данных нет.
+        pub async fn download(
+            &self,
+            instrument: &InstrumentId,
+            year: i32,
+        ) -> Result<Option<Vec<u8>>, HistoryDataError> {
+            let response = self
+                .http
+                .get(format!("{}/history-data", self.base_url))
+                .query(&[
+                    ("instrumentId", instrument.to_string()),
+                    ("year", year.to_string()),
+                ])
+                .bearer_auth(&self.token)
+                .send()
+                .await?;
+            match response.status() {
+                reqwest::StatusCode::OK => Ok(Some(response.bytes().await?.to_vec())),
+                reqwest::StatusCode::NOT_FOUND => Ok(None),
+                status => Err(HistoryDataError::Status(status.as_u16())),
+            }
+        }
+
+        /// Свечи инструмента за год по возрастанию времени.
+        pub async fn candles(
+            &self,
+            instrument: &InstrumentId,
+            year: i32,
+        ) -> Result<Vec<Candle>, HistoryDataError> {
+            match self.download(instrument, year).await? {
+                Some(archive) => read_zip(Cursor::new(archive)),
+                None => Ok(Vec::new()),
+            }
+        }
+    }
+
+    impl From<reqwest::Error> for HistoryDataError {
+        fn from(error: reqwest::Error) -> Self {
+            Self::Http(error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::FileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    const UID: &str = "e6123145-9665-43e0-8413-cd61b8aa9b13";
+
+    fn archive(files: &[(&str, String)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, content) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_zipped_csv() {
+        let day1 = format!(
+            "{UID};2023-01-03T07:01:00Z;271.5;271.85;272.06;271.5;16553;\n\
+             {UID};2023-01-03T07:00:00Z;270;271.5;271.6;269.9;100;\n"
+        );
+        let day2 = format!("{UID};2023-01-04T07:00:00Z;272;273;274;271;5;\n\n");
+        let data = archive(&[
+            ("e6123145_20230104.csv", day2),
+            ("e6123145_20230103.csv", day1),
+        ]);
+        let candles = read_zip(Cursor::new(data)).unwrap();
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[0].time.to_rfc3339(), "2023-01-03T07:00:00+00:00");
+        let candle = candles[1];
+        assert_eq!(candle.open, Decimal::from_str("271.5").unwrap());
+        assert_eq!(candle.close, Decimal::from_str("271.85").unwrap());
+        assert_eq!(candle.high, Decimal::from_str("272.06").unwrap());
+        assert_eq!(candle.low, Decimal::from_str("271.5").unwrap());
+        assert_eq!(candle.volume, 16553);
+
+        let error = read_csv(format!("{UID};2023-01-03T07:00:00Z;x;1;1;1;1;").as_bytes());
+        assert!(matches!(
+            error,
+            Err(HistoryDataError::Parse { line: 1, .. })
+        ));
+    }
+
+    #[cfg(feature = "history-data-download")]
+    #[tokio::test]
+    async fn downloads_from_base_url() {
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        use crate::types::InstrumentId;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let body = archive(&[(
+            "e6123145_20230103.csv",
+            format!("{UID};2023-01-03T07:00:00Z;1;2;3;0.5;10;\n"),
+        )]);
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let read = stream.read(&mut request).unwrap();
+            let request = String::from_utf8_lossy(&request[..read]).into_owned();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+            request
+        });
+
+        let client = HistoryDataClient::new("token").with_base_url(format!("http://{address}/"));
+        let instrument: InstrumentId = UID.parse().unwrap();
+        let candles = client.candles(&instrument, 2023).await.unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].high, Decimal::from(3));
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with(&format!("GET /history-data?instrumentId={UID}&year=2023 ")));
+        assert!(request.contains("authorization: Bearer token"));
+    }
+}
@@ -0,0 +1,476 @@
+//! Технические индикаторы по свечам.
+//!
+//! Каждый индикатор — калькулятор, которому свечи передаются по одной ([`Indicator::next`]).
+//! Расчёт по срезу свечей ([`Indicator::calculate`]) делает то же самое, поэтому по истории
+//! и по свечам из стрима получаются одинаковые значения. Обновления несформированной свечи
+//! из стрима учитывает [`LiveIndicator`].
+//!
+//! Значения считаются в `f64`. Конструкторы возвращают `None` для нулевого периода.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use tinkoff_invest_grpc::decimal::rust_decimal::prelude::ToPrimitive;
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::types::{Candle, MoscowTime};
+
+/// Индикатор, который считается по свечам по возрастанию времени.
+pub trait Indicator {
+    type Output;
+
+    /// Учесть следующую свечу. `None`, пока свечей недостаточно.
+    fn next(&mut self, candle: &Candle) -> Option<Self::Output>;
+
+    /// Значения по каждой свече среза.
+    fn calculate(mut self, candles: &[Candle]) -> Vec<Option<Self::Output>>
+    where
+        Self: Sized,
+    {
+        candles.iter().map(|candle| self.next(candle)).collect()
+    }
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+fn close(candle: &Candle) -> f64 {
+    to_f64(candle.close)
+}
+
+/// Простое скользящее среднее цены закрытия.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Option<Self> {
+        (period > 0).then(|| Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        })
+    }
+
+    /// Учесть следующее значение.
+    pub fn next_value(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        self.next_value(close(candle))
+    }
+}
+
+/// Экспоненциальное скользящее среднее цены закрытия с коэффициентом `2 / (period + 1)`.
+/// Первое значение — простое среднее первых `period` цен.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Option<Self> {
+        Some(Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period)?,
+            value: None,
+        })
+    }
+
+    /// Учесть следующее значение.
+    pub fn next_value(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => self.seed.next_value(value),
+        };
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        self.next_value(close(candle))
+    }
+}
+
+/// Сглаживание Уайлдера: первое значение — простое среднее, дальше
+/// `(previous * (period - 1) + value) / period`.
+#[derive(Debug, Clone)]
+struct Wilder {
+    period: usize,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Option<Self> {
+        Some(Self {
+            period,
+            seed: Sma::new(period)?,
+            value: None,
+        })
+    }
+
+    fn next_value(&mut self, value: f64) -> Option<f64> {
+        let period = self.period as f64;
+        self.value = match self.value {
+            Some(previous) => Some((previous * (period - 1.0) + value) / period),
+            None => self.seed.next_value(value),
+        };
+        self.value
+    }
+}
+
+/// Индекс относительной силы по Уайлдеру, от 0 до 100.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    previous: Option<f64>,
+    gain: Wilder,
+    loss: Wilder,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Option<Self> {
+        Some(Self {
+            previous: None,
+            gain: Wilder::new(period)?,
+            loss: Wilder::new(period)?,
+        })
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        let close = close(candle);
+        let previous = self.previous.replace(close)?;
+        let change = close - previous;
+        let gain = self.gain.next_value(change.max(0.0));
+        let loss = self.loss.next_value((-change).max(0.0));
+        let (gain, loss) = (gain?, loss?);
+        if loss == 0.0 {
+            return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+        }
+        Some(100.0 - 100.0 / (1.0 + gain / loss))
+    }
+}
+
+/// Значение MACD.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    /// Разность быстрой и медленной EMA.
+    pub macd: f64,
+    /// EMA линии MACD.
+    pub signal: f64,
+    /// Разность линии MACD и сигнальной линии.
+    pub histogram: f64,
+}
+
+/// MACD: разность быстрой и медленной EMA цены закрытия и её сигнальная EMA.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    /// `None`, если один из периодов равен нулю.
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Option<Self> {
+        Some(Self {
+            fast: Ema::new(fast)?,
+            slow: Ema::new(slow)?,
+            signal: Ema::new(signal)?,
+        })
+    }
+}
+
+impl Default for Macd {
+    /// Стандартные периоды 12, 26 и 9.
+    fn default() -> Self {
+        Self::new(12, 26, 9).expect("Default periods are positive")
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn next(&mut self, candle: &Candle) -> Option<MacdValue> {
+        let close = close(candle);
+        let fast = self.fast.next_value(close);
+        let slow = self.slow.next_value(close);
+        let macd = fast? - slow?;
+        let signal = self.signal.next_value(macd)?;
+        Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
+
+/// Полосы Боллинджера.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub lower: f64,
+    /// Простое скользящее среднее.
+    pub middle: f64,
+    pub upper: f64,
+}
+
+/// Полосы Боллинджера: SMA цены закрытия ± `multiplier` стандартных отклонений
+/// (по генеральной совокупности).
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    multiplier: f64,
+    window: VecDeque<f64>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, multiplier: f64) -> Option<Self> {
+        (period > 0).then(|| Self {
+            period,
+            multiplier,
+            window: VecDeque::with_capacity(period + 1),
+        })
+    }
+}
+
+impl Default for Bollinger {
+    /// Стандартные 20 свечей и 2 отклонения.
+    fn default() -> Self {
+        Self::new(20, 2.0).expect("Default period is positive")
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerBands;
+
+    fn next(&mut self, candle: &Candle) -> Option<BollingerBands> {
+        self.window.push_back(close(candle));
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+        let period = self.period as f64;
+        let mean = self.window.iter().sum::<f64>() / period;
+        let variance = self
+            .window
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / period;
+        let width = self.multiplier * variance.sqrt();
+        Some(BollingerBands {
+            lower: mean - width,
+            middle: mean,
+            upper: mean + width,
+        })
+    }
+}
+
+/// Средний истинный диапазон со сглаживанием Уайлдера.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    previous_close: Option<f64>,
+    average: Wilder,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Option<Self> {
+        Some(Self {
+            previous_close: None,
+            average: Wilder::new(period)?,
+        })
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        let high = to_f64(candle.high);
+        let low = to_f64(candle.low);
+        let range = match self.previous_close {
+            Some(previous) => (high - low)
+                .max((high - previous).abs())
+                .max((low - previous).abs()),
+            None => high - low,
+        };
+        self.previous_close = Some(close(candle));
+        self.average.next_value(range)
+    }
+}
+
+/// Средняя цена, взвешенная по объёму, по типичной цене свечи `(high + low + close) / 3`.
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    daily: bool,
+    date: Option<NaiveDate>,
+    turnover: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    /// VWAP с начала расчёта.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// VWAP с начала торгового дня: сбрасывается в полночь по Москве.
+    pub fn daily() -> Self {
+        Self {
+            daily: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        if self.daily {
+            let date = candle.time.moscow_date();
+            if self.date.replace(date) != Some(date) {
+                self.turnover = 0.0;
+                self.volume = 0.0;
+            }
+        }
+        let typical = to_f64(candle.high + candle.low + candle.close) / 3.0;
+        let volume = candle.volume as f64;
+        self.turnover += typical * volume;
+        self.volume += volume;
+        (self.volume > 0.0).then(|| self.turnover / self.volume)
+    }
+}
+
+/// Индикатор по свечам из стрима. Свеча с тем же временем, что и предыдущая, заменяет её:
+/// так приходят обновления несформированной свечи. Свечи старше предыдущей пропускаются.
+#[derive(Debug, Clone)]
+pub struct LiveIndicator<I> {
+    current: I,
+    before_last: Option<I>,
+    last_time: Option<DateTime<Utc>>,
+}
+
+impl<I: Indicator + Clone> LiveIndicator<I> {
+    pub fn new(indicator: I) -> Self {
+        Self {
+            current: indicator,
+            before_last: None,
+            last_time: None,
+        }
+    }
+
+    /// Учесть новую свечу или обновление последней. `None`, пока свечей недостаточно
+    /// или если свеча старше последней.
+    pub fn update(&mut self, candle: &Candle) -> Option<I::Output> {
+        match self.last_time {
+            Some(last) if candle.time < last => return None,
+            Some(last) if candle.time == last => {
+                if let Some(before_last) = &self.before_last {
+                    self.current = before_last.clone();
+                }
+            }
+            _ => {}
+        }
+        self.before_last = Some(self.current.clone());
+        self.last_time = Some(candle.time);
+        self.current.next(candle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    use crate::test_fixtures::{at, candle};
+
+    fn series(closes: &[i64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| candle(at(3, 7, 0) + Duration::minutes(i as i64), close))
+            .collect()
+    }
+
+    #[test]
+    fn calculates_moving_averages() {
+        let candles = series(&[1, 2, 3, 4, 5]);
+        assert_eq!(
+            Sma::new(3).unwrap().calculate(&candles),
+            vec![None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+        // alpha = 0.5, начало — SMA(3) = 2
+        assert_eq!(
+            Ema::new(3).unwrap().calculate(&candles),
+            vec![None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+        let rsi = Rsi::new(2).unwrap().calculate(&series(&[1, 2, 3, 2]));
+        assert_eq!(rsi[..3], [None, None, Some(100.0)]);
+        // gain = 0.5, loss = 0.5
+        assert_eq!(rsi[3], Some(50.0));
+
+        let bands = Bollinger::new(2, 2.0).unwrap().calculate(&candles[..2]);
+        assert_eq!(
+            bands[1],
+            Some(BollingerBands {
+                lower: 0.5,
+                middle: 1.5,
+                upper: 2.5,
+            })
+        );
+        let atr = Atr::new(2).unwrap().calculate(&candles);
+        assert_eq!(atr[1], Some(2.0));
+        assert_eq!(Vwap::new().calculate(&candles)[4], Some(3.0));
+
+        assert!(Ema::new(0).is_none());
+        assert!(Rsi::new(0).is_none());
+        assert!(Atr::new(0).is_none());
+        assert!(Bollinger::new(0, 2.0).is_none());
+        assert!(Macd::new(12, 0, 9).is_none());
+    }
+
+    #[test]
+    fn live_updates_match_history() {
+        let history = series(&[10, 12, 11, 15, 14, 13, 16, 18, 17, 20]);
+        let expected = Macd::new(2, 4, 3).unwrap().calculate(&history);
+
+        let mut live = LiveIndicator::new(Macd::new(2, 4, 3).unwrap());
+        let mut actual = Vec::new();
+        for candle in &history {
+            // Несформированная свеча приходит раньше окончательной
+            let mut partial = *candle;
+            partial.close += Decimal::from(5);
+            partial.is_complete = false;
+            live.update(&partial);
+            actual.push(live.update(candle));
+        }
+        assert_eq!(actual, expected);
+        assert!(expected[5].is_some());
+    }
+}
@@ -1,15 +1,16 @@
+use std::collections::HashMap;
 use std::ops::{RangeBounds};
 
-use chrono::{NaiveDate};
-
 use tinkoff_invest_grpc::api::instruments_service_client::InstrumentsServiceClient;
 use tinkoff_invest_grpc::api;
 use tinkoff_invest_grpc::Inner;
 
-use crate::error::{ErrorType, TinkoffInvestError};
+use crate::cache::{Cache, CachedInstrumentsClient};
+use crate::error::ErrorType;
 use crate::shared::EasyConvert;
 use crate::shared::date_range_to_timestamp_pair;
-use crate::types::InstrumentsList;
+use crate::trading_calendar::TradingCalendar;
+use crate::types::{ClassCode, Figi, InstrumentUid, InstrumentsList, Ticker, TimestampBound};
 use crate::{
     service,
     types,
@@ -17,9 +18,9 @@ use crate::{
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InstrumentRequest {
-    Figi(String),
-    Ticker { id: String, class_code: String },
-    Uid(String),
+    Figi(Figi),
+    Ticker { id: Ticker, class_code: ClassCode },
+    Uid(InstrumentUid),
 }
 
 impl From<InstrumentRequest> for api::InstrumentRequest {
@@ -28,50 +29,71 @@ impl From<InstrumentRequest> for api::InstrumentRequest {
         match req {
             InstrumentRequest::Figi(figi) => {
                 request.set_id_type(api::InstrumentIdType::Figi);
-                request.id = figi;
+                request.id = figi.into();
             }
             InstrumentRequest::Ticker { id, class_code } => {
                 request.set_id_type(api::InstrumentIdType::Ticker);
-                request.id = id;
-                request.class_code = class_code;
+                request.id = id.into();
+                request.class_code = class_code.into();
             }
             InstrumentRequest::Uid(uid) => {
                 request.set_id_type(api::InstrumentIdType::Uid);
-                request.id = uid;
+                request.id = uid.into();
             }
         };
         request
     }
 }
 
+impl From<InstrumentsList> for api::InstrumentsRequest {
+    fn from(list: InstrumentsList) -> api::InstrumentsRequest {
+        let status = match list {
+            InstrumentsList::Base => api::InstrumentStatus::Base,
+            InstrumentsList::All => api::InstrumentStatus::All,
+        };
+        api::InstrumentsRequest {
+            instrument_status: status as i32,
+        }
+    }
+}
+
+// Не смотря на то, что поле instrument в ответах помечено как Option, если мы укажем не существующий id - нам вернётся 50002 код
+// По тому мы её обрабатываем и делаем Ok(None), а Err оставляем для всех остальных ошибок
+pub(crate) fn not_found_as_none<T>(result: crate::Result<T>) -> crate::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(error) => match error.error_type() {
+            ErrorType::NotFound => Ok(None),
+            _ => Err(error),
+        },
+    }
+}
+
 service!(InstrumentsClient, InstrumentsServiceClient<Inner>);
 impl InstrumentsClient {
+    /// Добавить кеширование справочных методов.
+    pub fn with_cache(self, cache: Cache) -> CachedInstrumentsClient {
+        CachedInstrumentsClient::new(self, cache)
+    }
+
     pub async fn bond_by(
         &mut self,
         request: InstrumentRequest,
     ) -> crate::Result<Option<types::Bond>> {
-        let req: api::InstrumentRequest = request.into();
-        match self.internal.bond_by(req).await.map_err(Into::into) {
-            Ok(response) => {
-                let inner = response.into_inner();
-                let bond = inner.instrument;
-                Ok(bond.map(Into::into))
-            }
-            // Не смотря на то, что поле bond помечено как Option, если мы укажем не существующий id - нам вернётся 50002 код
-            // По тому мы её обрабатываем и делаем Ok(None), а Err оставляем для всех остальных ошибок
-            Err(error) => {
-                let error: TinkoffInvestError = error;
-                match error.error_type() {
-                    ErrorType::NotFound => Ok(None),
-                    _ => Err(error),
-                }
-            }
-        }
+        let response = self.bond_by_raw(request.into()).await;
+        Ok(not_found_as_none(response)?.and_then(|r| r.instrument.map(Into::into)))
+    }
+
+    pub(crate) async fn bond_by_raw(
+        &mut self,
+        req: api::InstrumentRequest,
+    ) -> crate::Result<api::BondResponse> {
+        Ok(self.internal.bond_by(req).await?.into_inner())
     }
 
-    pub async fn trading_schedules_all(
+    pub async fn trading_schedules_all<T: TimestampBound>(
         &mut self,
-        range: impl RangeBounds<NaiveDate>,
+        range: impl RangeBounds<T>,
     ) -> crate::Result<Vec<types::TradingSchedule>> {
         let (start, end) = date_range_to_timestamp_pair(range);
         let req = api::TradingSchedulesRequest {
@@ -86,16 +108,22 @@ impl InstrumentsClient {
         &mut self,
         req: api::TradingSchedulesRequest,
     ) -> crate::Result<Vec<types::TradingSchedule>> {
-        let res = self.internal.trading_schedules(req).await?;
-        let data = res.into_inner();
+        let data = self.trading_schedules_raw(req).await?;
         let schedules = data.exchanges;
         Ok(schedules.convert())
     }
 
-    pub async fn trading_schedules(
+    pub(crate) async fn trading_schedules_raw(
+        &mut self,
+        req: api::TradingSchedulesRequest,
+    ) -> crate::Result<api::TradingSchedulesResponse> {
+        Ok(self.internal.trading_schedules(req).await?.into_inner())
+    }
+
+    pub async fn trading_schedules<T: TimestampBound>(
         &mut self,
         exchange: String,
-        range: impl RangeBounds<NaiveDate>,
+        range: impl RangeBounds<T>,
     ) -> crate::Result<Vec<types::TradingSchedule>> {
         let (start, end) = date_range_to_timestamp_pair(range);
         let req = api::TradingSchedulesRequest {
@@ -106,58 +134,107 @@ impl InstrumentsClient {
         self.trading_schedules_internal(req).await
     }
 
-    pub async fn bonds(&mut self, list: InstrumentsList) -> crate::Result<Vec<types::Bond>> {
-        let numeric_status = match list {
-            InstrumentsList::Base => api::InstrumentStatus::Base,
-            InstrumentsList::All => api::InstrumentStatus::All,
-        } as i32;
+    /// Торговый календарь биржи на интервал дат.
+    /// `None`, если расписания этой биржи нет в ответе. Календари всех бирж — [`Self::trading_calendars`].
+    pub async fn trading_calendar<T: TimestampBound>(
+        &mut self,
+        exchange: String,
+        range: impl RangeBounds<T>,
+    ) -> crate::Result<Option<TradingCalendar>> {
+        let schedules = self.trading_schedules(exchange.clone(), range).await?;
+        Ok(schedules
+            .iter()
+            .find(|schedule| schedule.exchange().eq_ignore_ascii_case(&exchange))
+            .map(TradingCalendar::from))
+    }
 
-        let response = self
-            .internal
-            .bonds(api::InstrumentsRequest {
-                instrument_status: numeric_status,
-            })
-            .await?;
-        let data = response.into_inner();
+    /// Торговые календари всех бирж на интервал дат, по названию биржи.
+    pub async fn trading_calendars<T: TimestampBound>(
+        &mut self,
+        range: impl RangeBounds<T>,
+    ) -> crate::Result<HashMap<String, TradingCalendar>> {
+        let schedules = self.trading_schedules_all(range).await?;
+        Ok(schedules
+            .iter()
+            .map(|schedule| (schedule.exchange().to_owned(), schedule.into()))
+            .collect())
+    }
+
+    pub async fn bonds(&mut self, list: InstrumentsList) -> crate::Result<Vec<types::Bond>> {
+        let data = self.bonds_raw(list.into()).await?;
         let bonds = data.instruments;
         Ok(bonds.convert())
     }
 
-    pub async fn get_bond_coupons(
+    pub(crate) async fn bonds_raw(
+        &mut self,
+        req: api::InstrumentsRequest,
+    ) -> crate::Result<api::BondsResponse> {
+        Ok(self.internal.bonds(req).await?.into_inner())
+    }
+
+    pub async fn get_bond_coupons<T: TimestampBound>(
         &mut self,
-        figi: String,
-        range: impl RangeBounds<NaiveDate>,
+        figi: &Figi,
+        range: impl RangeBounds<T>,
     ) -> crate::Result<Vec<types::Coupon>> {
         let (start, end) = date_range_to_timestamp_pair(range);
-        let response = self
-            .internal
-            .get_bond_coupons(api::GetBondCouponsRequest {
-                figi: figi,
+        let data = self
+            .get_bond_coupons_raw(api::GetBondCouponsRequest {
+                figi: figi.to_string(),
                 from: start,
                 to: end,
             })
             .await?;
-        let data = response.into_inner();
         let coupons = data.events;
         Ok(coupons.convert())
     }
 
+    pub(crate) async fn get_bond_coupons_raw(
+        &mut self,
+        req: api::GetBondCouponsRequest,
+    ) -> crate::Result<api::GetBondCouponsResponse> {
+        Ok(self.internal.get_bond_coupons(req).await?.into_inner())
+    }
+
     pub async fn currency_by() {}
 
     pub async fn currencies() {}
 
     pub async fn etf_by() {}
     pub async fn etfs() {}
-    pub async fn future_by() {}
 
-    pub async fn futures() {}
+    pub async fn future_by(
+        &mut self,
+        request: InstrumentRequest,
+    ) -> crate::Result<Option<types::Future>> {
+        let req: api::InstrumentRequest = request.into();
+        let response = self.internal.future_by(req).await.map_err(Into::into);
+        Ok(not_found_as_none(response)?.and_then(|r| r.into_inner().instrument.map(Into::into)))
+    }
+
+    pub async fn futures(&mut self, list: InstrumentsList) -> crate::Result<Vec<types::Future>> {
+        let response = self.internal.futures(api::InstrumentsRequest::from(list)).await?;
+        let data = response.into_inner();
+        let futures = data.instruments;
+        Ok(futures.convert())
+    }
 
     pub async fn share_by() {}
 
     pub async fn shares() {}
     pub async fn get_accrues_interests() {}
 
-    pub async fn get_futures_margin() {}
+    /// Размер гарантийного обеспечения и стоимость шага цены фьючерса.
+    pub async fn get_futures_margin(&mut self, figi: &Figi) -> crate::Result<types::FuturesMargin> {
+        let response = self
+            .internal
+            .get_futures_margin(api::GetFuturesMarginRequest {
+                figi: figi.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().into())
+    }
     pub async fn get_instrument_by() {}
     pub async fn get_dividends() {}
 
@@ -1,6 +1,24 @@
 mod generated;
+#[cfg(feature = "serde")]
+mod serialization;
 mod shared;
+pub mod bond_analytics;
+pub mod cache;
+#[cfg(feature = "candle-store")]
+pub mod candle_store;
+pub mod candle_validation;
+pub mod candles_history;
+pub mod cash_flow_projection;
+#[cfg(feature = "history-data")]
+pub mod history_data;
+pub mod indicators;
 pub mod instruments;
+pub mod margin;
+pub mod market_data;
+pub mod resample;
+#[cfg(test)]
+mod test_fixtures;
+pub mod trading_calendar;
 pub mod types;
 pub mod users;
 
@@ -9,6 +27,7 @@ pub use generated::errors as error;
 
 use error::TinkoffInvestError;
 use instruments::InstrumentsClient;
+use market_data::MarketDataClient;
 pub use chrono;
 pub use tinkoff_invest_grpc::decimal;
 pub struct TinkoffInvestClient {
@@ -18,6 +37,7 @@ pub struct TinkoffInvestClient {
 pub type Result<T> = core::result::Result<T, TinkoffInvestError>;
 macro_rules! service {
     ($name:ident, $internal:ty $(, {$($impl: item)*$(;)*})?) => {
+        #[derive(Clone)]
         pub struct $name {
             internal: $internal
         }
@@ -45,6 +65,104 @@ macro_rules! service_getter {
     };
 }
 
+/// Перечисление поверх enum из proto: значения, которых нет в SDK, сохраняются в `Unknown`.
+///
+/// Вариант без `= ...` соответствует одноимённому варианту proto.
+macro_rules! proto_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident: $api:ident { $($variants:tt)* }
+    ) => {
+        $crate::proto_enum!(@munch [$(#[$meta])*] $name $api [] $($variants)*);
+    };
+    (@munch $meta:tt $name:ident $api:ident [$($done:tt)*]
+        $(#[$variant_meta:meta])* $variant:ident = $api_variant:ident, $($rest:tt)*
+    ) => {
+        $crate::proto_enum!(@munch $meta $name $api [$($done)* ([$(#[$variant_meta])*] $variant $api_variant)] $($rest)*);
+    };
+    (@munch $meta:tt $name:ident $api:ident [$($done:tt)*]
+        $(#[$variant_meta:meta])* $variant:ident, $($rest:tt)*
+    ) => {
+        $crate::proto_enum!(@munch $meta $name $api [$($done)* ([$(#[$variant_meta])*] $variant $variant)] $($rest)*);
+    };
+    (@munch [$($meta:tt)*] $name:ident $api:ident [$(([$($variant_meta:tt)*] $variant:ident $api_variant:ident))*]) => {
+        $($meta)*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum $name {
+            $(
+                $($variant_meta)*
+                $variant,
+            )*
+            /// Значение, неизвестное этой версии SDK.
+            Unknown(i32),
+        }
+
+        impl From<i32> for $name {
+            fn from(value: i32) -> Self {
+                match tinkoff_invest_grpc::api::$api::from_i32(value) {
+                    $(Some(tinkoff_invest_grpc::api::$api::$api_variant) => Self::$variant,)*
+                    #[allow(unreachable_patterns)]
+                    _ => Self::Unknown(value),
+                }
+            }
+        }
+
+        impl From<tinkoff_invest_grpc::api::$api> for $name {
+            fn from(value: tinkoff_invest_grpc::api::$api) -> Self {
+                (value as i32).into()
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> i32 {
+                match value {
+                    $($name::$variant => tinkoff_invest_grpc::api::$api::$api_variant as i32,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        /// Сериализуется именем варианта, неизвестное значение — числом.
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $($name::$variant => serializer.serialize_str(stringify!($variant)),)*
+                    $name::Unknown(value) => serializer.serialize_i32(*value),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                use $crate::serialization::EnumRepr;
+                match EnumRepr::deserialize(deserializer)? {
+                    $(EnumRepr::Name(name) if name == stringify!($variant) => Ok($name::$variant),)*
+                    EnumRepr::Name(name) => Err(serde::de::Error::unknown_variant(
+                        &name,
+                        &[$(stringify!($variant)),*],
+                    )),
+                    EnumRepr::Code(value) => Ok(value.into()),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl $crate::serialization::FromRaw<i32> for $name {
+            fn from_raw(raw: &i32) -> Self {
+                (*raw).into()
+            }
+
+            fn into_raw(self) -> Result<i32, String> {
+                Ok(self.into())
+            }
+        }
+    };
+}
+pub(crate) use proto_enum;
+
 impl TinkoffInvestClient {
     pub async fn connect(token: &str) -> core::result::Result<Self, Box<dyn std::error::Error>> {
         let internal = tinkoff_invest_grpc::TinkoffInvestClient::connect(token).await?;
@@ -52,7 +170,7 @@ impl TinkoffInvestClient {
     }
     service_getter!(users, UsersClient);
     service_getter!(instruments, InstrumentsClient);
-    // market_data
+    service_getter!(market_data, MarketDataClient);
     // market_data_stream
     // operations
     // operations_stream
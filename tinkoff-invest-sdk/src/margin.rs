@@ -0,0 +1,303 @@
+//! Оценка маржинальных показателей счёта до отправки заявки.
+//!
+//! Модель упрощённая: начальная маржа позиции — её стоимость, умноженная на ставку риска
+//! начальной маржи (`dlong_min`/`dshort_min`), минимальная — на ставку минимальной маржи
+//! (`dlong`/`dshort`). Обе ставки умножаются на коэффициент ставки риска (`klong`/`kshort`).
+//! К текущим показателям из [`MarginAttributes`] прибавляется только изменение маржи
+//! по инструменту заявки; денежная позиция и комиссии не учитываются.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::types::{Bond, Figi, Future, Long, MarginAttributes, Short};
+
+/// Ставки риска инструмента.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RiskRates {
+    pub long: Option<Long>,
+    /// `None`, если инструмент недоступен для коротких продаж.
+    pub short: Option<Short>,
+}
+
+impl From<&Bond> for RiskRates {
+    fn from(bond: &Bond) -> Self {
+        Self {
+            long: bond.long(),
+            short: bond.short(),
+        }
+    }
+}
+
+impl From<&Future> for RiskRates {
+    fn from(future: &Future) -> Self {
+        Self {
+            long: future.long(),
+            short: future.short(),
+        }
+    }
+}
+
+/// Текущая позиция по инструменту.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginPosition {
+    pub figi: Figi,
+    /// Количество в штуках, для короткой позиции отрицательное.
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderDirection {
+    Buy,
+    Sell,
+}
+
+/// Заявка, влияние которой нужно оценить.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HypotheticalOrder {
+    pub figi: Figi,
+    pub direction: OrderDirection,
+    /// Количество в штуках.
+    pub quantity: Decimal,
+    /// Цена одной штуки в валюте счёта.
+    pub price: Decimal,
+    pub rates: RiskRates,
+}
+
+/// Маржинальные показатели счёта после исполнения заявки.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginEstimate {
+    pub liquid_portfolio: Decimal,
+    pub starting_margin: Decimal,
+    pub minimal_margin: Decimal,
+    /// Уровень достаточности средств. `None`, если начальная маржа нулевая.
+    pub funds_sufficiency_level: Option<Decimal>,
+    /// Сколько не хватает до начальной маржи.
+    pub amount_of_missing_funds: Decimal,
+}
+
+impl MarginEstimate {
+    /// Хватает ли средств на начальную маржу: новая сделка будет разрешена.
+    pub fn is_sufficient(&self) -> bool {
+        self.amount_of_missing_funds.is_zero()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MarginError {
+    /// API не вернул маржинальный показатель счёта.
+    MissingAttribute(&'static str),
+    /// У инструмента нет ставок риска для длинной позиции.
+    LongNotAvailable { figi: Figi },
+    /// Инструмент недоступен для коротких продаж.
+    ShortNotAvailable { figi: Figi },
+}
+
+impl fmt::Display for MarginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAttribute(name) => write!(f, "margin attribute {name} is missing"),
+            Self::LongNotAvailable { figi } => write!(f, "{figi} has no long risk rates"),
+            Self::ShortNotAvailable { figi } => write!(f, "{figi} is not available for short"),
+        }
+    }
+}
+
+impl std::error::Error for MarginError {}
+
+pub struct MarginCalculator {
+    liquid_portfolio: Decimal,
+    starting_margin: Decimal,
+    minimal_margin: Decimal,
+    positions: HashMap<Figi, Decimal>,
+}
+
+impl MarginCalculator {
+    pub fn new(
+        attributes: &MarginAttributes,
+        positions: impl IntoIterator<Item = MarginPosition>,
+    ) -> Result<Self, MarginError> {
+        let value = |money: Option<crate::types::MoneyValue<'_>>, name| {
+            money
+                .map(|money| money.value())
+                .ok_or(MarginError::MissingAttribute(name))
+        };
+        let mut quantities = HashMap::new();
+        for position in positions {
+            *quantities.entry(position.figi).or_default() += position.quantity;
+        }
+        Ok(Self {
+            liquid_portfolio: value(attributes.liquid_portfolio(), "liquid_portfolio")?,
+            starting_margin: value(attributes.starting_margin(), "starting_margin")?,
+            minimal_margin: value(attributes.minimal_margin(), "minimal_margin")?,
+            positions: quantities,
+        })
+    }
+
+    /// Показатели счёта после исполнения заявки.
+    /// Маржа по инструменту до и после сделки считается по цене заявки.
+    pub fn estimate(&self, order: &HypotheticalOrder) -> Result<MarginEstimate, MarginError> {
+        let before = self.positions.get(&order.figi).copied().unwrap_or_default();
+        let after = match order.direction {
+            OrderDirection::Buy => before + order.quantity,
+            OrderDirection::Sell => before - order.quantity,
+        };
+        let (starting_before, minimal_before) = position_margin(order, before)?;
+        let (starting_after, minimal_after) = position_margin(order, after)?;
+
+        let starting_margin = self.starting_margin + starting_after - starting_before;
+        let minimal_margin = self.minimal_margin + minimal_after - minimal_before;
+        let funds_sufficiency_level =
+            (!starting_margin.is_zero()).then(|| self.liquid_portfolio / starting_margin);
+        Ok(MarginEstimate {
+            liquid_portfolio: self.liquid_portfolio,
+            starting_margin,
+            minimal_margin,
+            funds_sufficiency_level,
+            amount_of_missing_funds: (starting_margin - self.liquid_portfolio).max(Decimal::ZERO),
+        })
+    }
+}
+
+/// Начальная и минимальная маржа позиции `quantity` по цене заявки.
+fn position_margin(
+    order: &HypotheticalOrder,
+    quantity: Decimal,
+) -> Result<(Decimal, Decimal), MarginError> {
+    let value = quantity.abs() * order.price;
+    if quantity.is_zero() {
+        Ok((Decimal::ZERO, Decimal::ZERO))
+    } else if quantity.is_sign_positive() {
+        let long = order
+            .rates
+            .long
+            .ok_or_else(|| MarginError::LongNotAvailable {
+                figi: order.figi.clone(),
+            })?;
+        let value = value * long.klong;
+        Ok((value * long.dlong_min, value * long.dlong))
+    } else {
+        let short = order
+            .rates
+            .short
+            .ok_or_else(|| MarginError::ShortNotAvailable {
+                figi: order.figi.clone(),
+            })?;
+        let value = value * short.kshort;
+        Ok((value * short.dshort_min, value * short.dshort))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tinkoff_invest_grpc::api;
+
+    fn rub(units: i64) -> Option<api::MoneyValue> {
+        Some(api::MoneyValue {
+            currency: "rub".to_owned(),
+            units,
+            nano: 0,
+        })
+    }
+
+    fn figi() -> Figi {
+        "BBG004730N88".parse().unwrap()
+    }
+
+    fn calculator(position: i64) -> MarginCalculator {
+        let attributes: MarginAttributes = api::GetMarginAttributesResponse {
+            liquid_portfolio: rub(100_000),
+            starting_margin: rub(20_000),
+            minimal_margin: rub(10_000),
+            ..Default::default()
+        }
+        .into();
+        let position = MarginPosition {
+            figi: figi(),
+            quantity: Decimal::from(position),
+        };
+        MarginCalculator::new(&attributes, [position]).unwrap()
+    }
+
+    fn order(direction: OrderDirection, quantity: i64, short: bool) -> HypotheticalOrder {
+        order_with_coefficient(direction, quantity, short, Decimal::ONE)
+    }
+
+    fn order_with_coefficient(
+        direction: OrderDirection,
+        quantity: i64,
+        short: bool,
+        coefficient: Decimal,
+    ) -> HypotheticalOrder {
+        let rate = |percent| Decimal::new(percent, 2);
+        HypotheticalOrder {
+            figi: figi(),
+            direction,
+            quantity: Decimal::from(quantity),
+            price: Decimal::from(1000),
+            rates: RiskRates {
+                long: Some(Long {
+                    klong: coefficient,
+                    dlong: rate(12),
+                    dlong_min: rate(25),
+                }),
+                short: short.then(|| Short {
+                    kshort: coefficient,
+                    dshort: rate(25),
+                    dshort_min: rate(50),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn buying_increases_margin() {
+        let estimate = calculator(0)
+            .estimate(&order(OrderDirection::Buy, 10, false))
+            .unwrap();
+        assert_eq!(estimate.starting_margin, Decimal::from(22_500));
+        assert_eq!(estimate.minimal_margin, Decimal::from(11_200));
+        assert_eq!(
+            estimate.funds_sufficiency_level,
+            Some(Decimal::from(100_000) / Decimal::from(22_500))
+        );
+        assert!(estimate.is_sufficient());
+    }
+
+    #[test]
+    fn risk_coefficient_scales_rates() {
+        let estimate = calculator(0)
+            .estimate(&order_with_coefficient(
+                OrderDirection::Sell,
+                10,
+                true,
+                Decimal::from(2),
+            ))
+            .unwrap();
+        assert_eq!(estimate.starting_margin, Decimal::from(30_000));
+        assert_eq!(estimate.minimal_margin, Decimal::from(15_000));
+    }
+
+    #[test]
+    fn closing_short_releases_margin() {
+        let estimate = calculator(-10)
+            .estimate(&order(OrderDirection::Buy, 10, true))
+            .unwrap();
+        assert_eq!(estimate.starting_margin, Decimal::from(15_000));
+        assert_eq!(estimate.minimal_margin, Decimal::from(7_500));
+    }
+
+    #[test]
+    fn short_requires_short_rates() {
+        let result = calculator(0).estimate(&order(OrderDirection::Sell, 1, false));
+        assert_eq!(
+            result,
+            Err(MarginError::ShortNotAvailable {
+                figi: figi()
+            })
+        );
+    }
+}
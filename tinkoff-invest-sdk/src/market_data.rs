@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::{Range, RangeBounds};
+
+use chrono::{DateTime, Duration, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use tinkoff_invest_grpc::api;
+use tinkoff_invest_grpc::api::market_data_service_client::MarketDataServiceClient;
+use tinkoff_invest_grpc::Inner;
+
+use crate::candles_history::CandlesHistory;
+use crate::service;
+use crate::shared::date_range_to_timestamp_pair;
+use crate::types::{
+    Candle, CandleInterval, ClosePrice, InstrumentId, InstrumentUid, LastPrice, OrderBook,
+    TimestampBound, Trade, TradingStatus,
+};
+
+/// Наибольший интервал в часах, за который API отдаёт обезличенные сделки одним запросом.
+const LAST_TRADES_MAX_SPAN_HOURS: i64 = 1;
+/// Сколько инструментов передаётся в одном запросе цен и статусов.
+const BATCH_SIZE: usize = 300;
+/// Сколько запросов цен и статусов выполняется одновременно.
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+service!(MarketDataClient, MarketDataServiceClient<Inner>);
+impl MarketDataClient {
+    /// Свечи инструмента за интервал времени.
+    ///
+    /// Интервал не должен превышать [`CandleInterval::max_request_span`], иначе API вернёт ошибку.
+    pub async fn get_candles<T: TimestampBound>(
+        &mut self,
+        instrument: impl Into<InstrumentId>,
+        interval: CandleInterval,
+        range: impl RangeBounds<T>,
+    ) -> crate::Result<Vec<Candle>> {
+        let (from, to) = date_range_to_timestamp_pair(range);
+        let request = api::GetCandlesRequest {
+            instrument_id: instrument.into().to_string(),
+            interval: interval.into(),
+            from,
+            to,
+            ..Default::default()
+        };
+        let data = self.get_candles_raw(request).await?;
+        Ok(data.candles.iter().filter_map(Candle::from_api).collect())
+    }
+
+    /// История свечей за интервал любой длины: интервал разбивается на запросы, свечи приходят
+    /// потоком по возрастанию времени. Пропуск неторговых дней, лимит запросов и продолжение
+    /// прерванной загрузки настраиваются через [`CandlesHistory`].
+    pub fn candles_history(
+        &self,
+        instrument: impl Into<InstrumentId>,
+        interval: CandleInterval,
+        range: Range<DateTime<Utc>>,
+    ) -> CandlesHistory {
+        CandlesHistory::new(self.clone(), instrument.into(), interval, range)
+    }
+
+    /// Стакан по инструменту. Глубина — 1, 10, 20, 30, 40 или 50 уровней.
+    pub async fn get_order_book(
+        &mut self,
+        instrument: impl Into<InstrumentId>,
+        depth: i32,
+    ) -> crate::Result<OrderBook> {
+        let request = api::GetOrderBookRequest {
+            instrument_id: instrument.into().to_string(),
+            depth,
+            ..Default::default()
+        };
+        Ok(self.get_order_book_raw(request).await?.into())
+    }
+
+    /// Обезличенные сделки за интервал по возрастанию времени. Интервал длиннее часа
+    /// запрашивается по частям; API отдаёт сделки только за последний час.
+    pub async fn get_last_trades(
+        &mut self,
+        instrument: impl Into<InstrumentId>,
+        range: Range<DateTime<Utc>>,
+    ) -> crate::Result<Vec<Trade>> {
+        let instrument_id = instrument.into().to_string();
+        let mut trades = Vec::new();
+        for chunk in plan_last_trades_chunks(range) {
+            let (from, to) = date_range_to_timestamp_pair(chunk);
+            let request = api::GetLastTradesRequest {
+                instrument_id: instrument_id.clone(),
+                from,
+                to,
+                ..Default::default()
+            };
+            let response = self.get_last_trades_raw(request).await?;
+            trades.extend(response.trades.iter().filter_map(Trade::from_api));
+        }
+        trades.sort_by_key(|trade| trade.time);
+        Ok(trades)
+    }
+
+    /// Цены последних сделок по инструментам по uid. Инструменты запрашиваются пачками
+    /// по несколько запросов одновременно. Инструментов без цены в ответе нет.
+    pub async fn last_prices<I: Into<InstrumentId>>(
+        &self,
+        instruments: impl IntoIterator<Item = I>,
+    ) -> crate::Result<HashMap<InstrumentUid, LastPrice>> {
+        let prices = self
+            .batched(instruments, |mut client, instrument_id| async move {
+                let request = api::GetLastPricesRequest {
+                    instrument_id,
+                    ..Default::default()
+                };
+                let response = client.get_last_prices_raw(request).await?;
+                Ok(response.last_prices)
+            })
+            .await?;
+        Ok(prices
+            .into_iter()
+            .filter_map(LastPrice::from_api)
+            .map(|price| (price.instrument_uid.clone(), price))
+            .collect())
+    }
+
+    /// Цены закрытия торговой сессии по инструментам по uid, см. [`Self::last_prices`].
+    pub async fn close_prices<I: Into<InstrumentId>>(
+        &self,
+        instruments: impl IntoIterator<Item = I>,
+    ) -> crate::Result<HashMap<InstrumentUid, ClosePrice>> {
+        let prices = self
+            .batched(instruments, |mut client, instrument_ids| async move {
+                let request = api::GetClosePricesRequest {
+                    instruments: instrument_ids
+                        .into_iter()
+                        .map(|instrument_id| api::InstrumentClosePriceRequest { instrument_id })
+                        .collect(),
+                };
+                let response = client.get_close_prices_raw(request).await?;
+                Ok(response.close_prices)
+            })
+            .await?;
+        Ok(prices
+            .into_iter()
+            .filter_map(ClosePrice::from_api)
+            .map(|price| (price.instrument_uid.clone(), price))
+            .collect())
+    }
+
+    /// Торговые статусы инструментов по uid, см. [`Self::last_prices`].
+    pub async fn trading_statuses<I: Into<InstrumentId>>(
+        &self,
+        instruments: impl IntoIterator<Item = I>,
+    ) -> crate::Result<HashMap<InstrumentUid, TradingStatus>> {
+        let statuses = self
+            .batched(instruments, |mut client, instrument_id| async move {
+                let request = api::GetTradingStatusesRequest { instrument_id };
+                let response = client.get_trading_statuses_raw(request).await?;
+                Ok(response.trading_statuses)
+            })
+            .await?;
+        Ok(statuses
+            .into_iter()
+            .map(|status| {
+                let status = TradingStatus::from(status);
+                (status.instrument_uid.clone(), status)
+            })
+            .collect())
+    }
+
+    /// Разбить инструменты на пачки по [`BATCH_SIZE`] и выполнить запросы,
+    /// не больше [`MAX_CONCURRENT_REQUESTS`] одновременно.
+    async fn batched<I, T, F, Fut>(
+        &self,
+        instruments: impl IntoIterator<Item = I>,
+        request: F,
+    ) -> crate::Result<Vec<T>>
+    where
+        I: Into<InstrumentId>,
+        F: Fn(Self, Vec<String>) -> Fut,
+        Fut: Future<Output = crate::Result<Vec<T>>>,
+    {
+        let instruments: Vec<String> = instruments
+            .into_iter()
+            .map(|instrument| instrument.into().to_string())
+            .collect();
+        run_batched(&instruments, BATCH_SIZE, |batch| {
+            request(self.clone(), batch)
+        })
+        .await
+    }
+
+    pub(crate) async fn get_candles_raw(
+        &mut self,
+        req: api::GetCandlesRequest,
+    ) -> crate::Result<api::GetCandlesResponse> {
+        Ok(self.internal.get_candles(req).await?.into_inner())
+    }
+
+    pub(crate) async fn get_order_book_raw(
+        &mut self,
+        req: api::GetOrderBookRequest,
+    ) -> crate::Result<api::GetOrderBookResponse> {
+        Ok(self.internal.get_order_book(req).await?.into_inner())
+    }
+
+    pub(crate) async fn get_last_prices_raw(
+        &mut self,
+        req: api::GetLastPricesRequest,
+    ) -> crate::Result<api::GetLastPricesResponse> {
+        Ok(self.internal.get_last_prices(req).await?.into_inner())
+    }
+
+    pub(crate) async fn get_close_prices_raw(
+        &mut self,
+        req: api::GetClosePricesRequest,
+    ) -> crate::Result<api::GetClosePricesResponse> {
+        Ok(self.internal.get_close_prices(req).await?.into_inner())
+    }
+
+    pub(crate) async fn get_trading_statuses_raw(
+        &mut self,
+        req: api::GetTradingStatusesRequest,
+    ) -> crate::Result<api::GetTradingStatusesResponse> {
+        Ok(self.internal.get_trading_statuses(req).await?.into_inner())
+    }
+
+    pub(crate) async fn get_last_trades_raw(
+        &mut self,
+        req: api::GetLastTradesRequest,
+    ) -> crate::Result<api::GetLastTradesResponse> {
+        Ok(self.internal.get_last_trades(req).await?.into_inner())
+    }
+}
+
+/// Части интервала, каждая не длиннее [`LAST_TRADES_MAX_SPAN_HOURS`] часов.
+fn plan_last_trades_chunks(range: Range<DateTime<Utc>>) -> Vec<Range<DateTime<Utc>>> {
+    let mut chunks = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let end = (start + Duration::hours(LAST_TRADES_MAX_SPAN_HOURS)).min(range.end);
+        chunks.push(start..end);
+        start = end;
+    }
+    chunks
+}
+
+/// Разбить идентификаторы на пачки по `batch_size`, выполнить запрос для каждой пачки,
+/// не больше [`MAX_CONCURRENT_REQUESTS`] одновременно, и объединить ответы.
+async fn run_batched<T, F, Fut>(
+    ids: &[String],
+    batch_size: usize,
+    request: F,
+) -> crate::Result<Vec<T>>
+where
+    F: Fn(Vec<String>) -> Fut,
+    Fut: Future<Output = crate::Result<Vec<T>>>,
+{
+    let batches: Vec<Vec<T>> = stream::iter(ids.chunks(batch_size))
+        .map(|batch| request(batch.to_vec()))
+        .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+    Ok(batches.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn splits_last_trades_range_into_hours() {
+        let at = |hour, minute| Utc.with_ymd_and_hms(2023, 3, 3, hour, minute, 0).unwrap();
+        assert_eq!(
+            plan_last_trades_chunks(at(7, 30)..at(9, 45)),
+            vec![
+                at(7, 30)..at(8, 30),
+                at(8, 30)..at(9, 30),
+                at(9, 30)..at(9, 45)
+            ]
+        );
+        assert_eq!(
+            plan_last_trades_chunks(at(7, 0)..at(8, 0)),
+            vec![at(7, 0)..at(8, 0)]
+        );
+        assert!(plan_last_trades_chunks(at(8, 0)..at(8, 0)).is_empty());
+        assert!(plan_last_trades_chunks(at(9, 0)..at(8, 0)).is_empty());
+    }
+
+    fn ids(count: usize) -> Vec<String> {
+        (0..count).map(|i| i.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn splits_ids_into_batches_and_flattens_responses() {
+        let mut sizes = run_batched(&ids(7), 3, |batch| async move { Ok(vec![batch.len()]) })
+            .await
+            .unwrap();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 3, 3]);
+
+        let mut echoed = run_batched(&ids(7), 3, |batch| async move { Ok(batch) })
+            .await
+            .unwrap();
+        echoed.sort_by_key(|id| id.parse::<usize>().unwrap());
+        assert_eq!(echoed, ids(7));
+
+        let empty: Vec<String> = run_batched(&[], 3, |batch| async move { Ok(batch) })
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fails_if_any_batch_fails() {
+        let result: crate::Result<Vec<String>> = run_batched(&ids(5), 2, |batch| async move {
+            if batch.contains(&"4".to_owned()) {
+                Err(tonic::Status::unavailable("тест").into())
+            } else {
+                Ok(batch)
+            }
+        })
+        .await;
+        assert!(result.is_err());
+    }
+}
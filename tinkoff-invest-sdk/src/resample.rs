@@ -0,0 +1,227 @@
+//! Сборка свечей крупного интервала из мелких: 5 минут, час или произвольный интервал из
+//! минутных свечей, дни и недели — из часовых.
+//!
+//! Open — первой свечи, close — последней, high и low — экстремумы, объём — сумма.
+//! Время свечи — начало её интервала.
+
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+
+use crate::trading_calendar::TradingCalendar;
+use crate::types::{moscow_day_start, Candle, MoscowTime};
+
+/// Интервал собираемых свечей. Нулевой интервал задать нельзя, см. [`Timeframe::minutes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Timeframe {
+    Minutes(NonZeroU32),
+    Hours(NonZeroU32),
+    /// День по Москве.
+    Day,
+    /// Неделя по Москве с понедельника.
+    Week,
+}
+
+impl Timeframe {
+    /// Интервал в `minutes` минут; `None`, если `minutes` равно нулю.
+    pub fn minutes(minutes: u32) -> Option<Self> {
+        NonZeroU32::new(minutes).map(Self::Minutes)
+    }
+
+    /// Интервал в `hours` часов; `None`, если `hours` равно нулю.
+    pub fn hours(hours: u32) -> Option<Self> {
+        NonZeroU32::new(hours).map(Self::Hours)
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Minutes(minutes) => Duration::minutes(i64::from(minutes.get())),
+            Self::Hours(hours) => Duration::hours(i64::from(hours.get())),
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+        }
+    }
+}
+
+/// Собрать свечи интервала `target` из более мелких свечей.
+/// Внутридневные интервалы отсчитываются от полуночи UTC.
+///
+/// Собранная свеча сформирована, если сформированы все её части и её интервал уже закончился.
+pub fn resample(candles: &[Candle], target: Timeframe) -> Vec<Candle> {
+    aggregate(candles, target, None, Utc::now())
+}
+
+/// Как [`resample`], но внутридневные интервалы отсчитываются от начала торговой сессии
+/// и обрываются на её конце: свеча не захватывает клиринг и ночь. День и неделя
+/// заканчиваются с концом последней сессии в них.
+/// Свечи вне сессий календаря собираются как в [`resample`].
+pub fn resample_sessions(
+    candles: &[Candle],
+    target: Timeframe,
+    calendar: &TradingCalendar,
+) -> Vec<Candle> {
+    aggregate(candles, target, Some(calendar), Utc::now())
+}
+
+fn aggregate(
+    candles: &[Candle],
+    target: Timeframe,
+    calendar: Option<&TradingCalendar>,
+    now: DateTime<Utc>,
+) -> Vec<Candle> {
+    let mut sorted: Vec<&Candle> = candles.iter().collect();
+    sorted.sort_by_key(|candle| candle.time);
+
+    let mut bars: Vec<(Range<DateTime<Utc>>, Candle)> = Vec::new();
+    for candle in sorted {
+        let range = bucket(candle.time, target, calendar);
+        match bars.last_mut() {
+            Some((last, bar)) if *last == range => {
+                bar.high = bar.high.max(candle.high);
+                bar.low = bar.low.min(candle.low);
+                bar.close = candle.close;
+                bar.volume += candle.volume;
+                bar.is_complete &= candle.is_complete;
+            }
+            _ => bars.push((
+                range.clone(),
+                Candle {
+                    time: range.start,
+                    ..*candle
+                },
+            )),
+        }
+    }
+    bars.into_iter()
+        .map(|(range, mut bar)| {
+            bar.is_complete &= finished_at(&range, calendar) <= now;
+            bar
+        })
+        .collect()
+}
+
+/// Когда заканчивается интервал свечи: с календарём — не позже конца последней сессии в нём.
+fn finished_at(range: &Range<DateTime<Utc>>, calendar: Option<&TradingCalendar>) -> DateTime<Utc> {
+    let last_session_end = calendar.and_then(|calendar| {
+        let sessions = calendar.sessions();
+        let index = sessions.partition_point(|session| session.start < range.end);
+        let session = sessions[..index].last()?;
+        (session.end > range.start).then_some(session.end)
+    });
+    last_session_end.map_or(range.end, |end| end.min(range.end))
+}
+
+fn bucket(
+    time: DateTime<Utc>,
+    target: Timeframe,
+    calendar: Option<&TradingCalendar>,
+) -> Range<DateTime<Utc>> {
+    let duration = target.duration();
+    match target {
+        Timeframe::Day => {
+            let start = moscow_day_start(time.moscow_date());
+            start..start + duration
+        }
+        Timeframe::Week => {
+            let date = time.moscow_date();
+            let monday = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+            let start = moscow_day_start(monday);
+            start..start + duration
+        }
+        Timeframe::Minutes(_) | Timeframe::Hours(_) => {
+            let session = calendar.and_then(|calendar| calendar.session_at(time));
+            let (origin, end) = match session {
+                Some(session) => (session.start, Some(session.end)),
+                None => (DateTime::<Utc>::from(UNIX_EPOCH), None),
+            };
+            let step = duration.num_milliseconds();
+            let offset = (time - origin).num_milliseconds();
+            let start = origin + Duration::milliseconds(offset - offset.rem_euclid(step));
+            let bar_end = start + duration;
+            start..end.map_or(bar_end, |end| end.min(bar_end))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tinkoff_invest_grpc::api;
+    use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+    use crate::test_fixtures::{at, calendar, candle, trading_day, ts};
+
+    #[test]
+    fn builds_bars_with_ohlcv() {
+        let mut candles: Vec<Candle> = (0..7)
+            .map(|i| candle(at(3, 7, i), 100 + i64::from(i)))
+            .collect();
+        candles.last_mut().unwrap().is_complete = false;
+        let bars = resample(&candles, Timeframe::minutes(5).unwrap());
+        assert_eq!(bars.len(), 2);
+        assert_eq!(
+            bars[0],
+            Candle {
+                open: Decimal::from(100),
+                high: Decimal::from(105),
+                low: Decimal::from(99),
+                close: Decimal::from(104),
+                volume: 5,
+                time: at(3, 7, 0),
+                is_complete: true,
+            }
+        );
+        assert_eq!(bars[1].time, at(3, 7, 5));
+        assert_eq!(bars[1].volume, 2);
+        assert!(!bars[1].is_complete);
+
+        // Пятница и понедельник попадают в разные недели, время — полночь по Москве
+        let hours = [candle(at(3, 10, 0), 1), candle(at(6, 10, 0), 2)];
+        let weeks = resample(&hours, Timeframe::Week);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[1].time, at(5, 21, 0));
+        // Неделя прошла, хотя часовых свечей до её конца нет
+        assert!(weeks[1].is_complete);
+        let days = resample(&hours[..1], Timeframe::Day);
+        assert_eq!(days[0].time, at(2, 21, 0));
+
+        assert_eq!(Timeframe::minutes(0), None);
+        assert_eq!(Timeframe::hours(0), None);
+    }
+
+    #[test]
+    fn does_not_span_clearing() {
+        let calendar = calendar([api::TradingDay {
+            clearing_start_time: ts(at(3, 11, 0)),
+            clearing_end_time: ts(at(3, 11, 5)),
+            ..trading_day(3, (7, 0), (15, 40))
+        }]);
+        let candles: Vec<Candle> = [(10, 30), (10, 59), (11, 5), (11, 40)]
+            .iter()
+            .map(|&(hour, minute)| candle(at(3, hour, minute), 100))
+            .collect();
+        let bars = resample_sessions(&candles, Timeframe::hours(1).unwrap(), &calendar);
+        let times: Vec<_> = bars.iter().map(|bar| bar.time).collect();
+        assert_eq!(times, vec![at(3, 10, 0), at(3, 11, 5)]);
+        assert_eq!(bars[1].volume, 2);
+    }
+
+    #[test]
+    fn completes_bars_when_their_interval_ends() {
+        let candles = [candle(at(3, 7, 0), 100), candle(at(3, 15, 0), 101)];
+        let five_minutes = Timeframe::minutes(5).unwrap();
+        let bars = aggregate(&candles[..1], five_minutes, None, at(3, 7, 3));
+        assert!(!bars[0].is_complete);
+        let bars = aggregate(&candles[..1], five_minutes, None, at(3, 7, 5));
+        assert!(bars[0].is_complete);
+
+        // После конца сессии день закончен, хотя полночь по Москве ещё не наступила
+        let calendar = calendar([trading_day(3, (7, 0), (15, 40))]);
+        let evening = at(3, 18, 0);
+        assert!(aggregate(&candles, Timeframe::Day, Some(&calendar), evening)[0].is_complete);
+        assert!(!aggregate(&candles, Timeframe::Day, None, evening)[0].is_complete);
+        assert!(!aggregate(&candles, Timeframe::Day, Some(&calendar), at(3, 15, 0))[0].is_complete);
+    }
+}
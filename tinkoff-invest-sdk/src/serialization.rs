@@ -0,0 +1,245 @@
+//! Представление типов SDK для serde: суммы и ставки — строками, даты и время — в ISO 8601,
+//! перечисления — именами вариантов.
+//!
+//! Обёртки над сообщениями proto сериализуются как структуры с полями сообщения. Поля, которых
+//! нет в представлении, при десериализации получают значения по умолчанию.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tinkoff_invest_grpc::api;
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::types::{
+    chrono_timestamp_to_grpc_timestamp, grpc_timestamp_to_chrono_timestamp, TimestampBound,
+};
+
+/// Поле сообщения proto в представлении для serde.
+pub(crate) trait FromRaw<Raw>: Sized {
+    fn from_raw(raw: &Raw) -> Self;
+
+    fn into_raw(self) -> Result<Raw, String>;
+}
+
+macro_rules! identity {
+    ($($ty:ty),*) => {
+        $(
+            impl FromRaw<$ty> for $ty {
+                #[inline]
+                fn from_raw(raw: &$ty) -> Self {
+                    raw.clone()
+                }
+
+                #[inline]
+                fn into_raw(self) -> Result<$ty, String> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+identity!(String, bool, i32, i64, Vec<String>);
+
+impl FromRaw<Option<api::Quotation>> for Option<Decimal> {
+    fn from_raw(raw: &Option<api::Quotation>) -> Self {
+        raw.as_ref().map(Into::into)
+    }
+
+    fn into_raw(self) -> Result<Option<api::Quotation>, String> {
+        self.map(|value| value.try_into().map_err(|error| format!("{error}")))
+            .transpose()
+    }
+}
+
+/// Денежная сумма: код валюты в том виде, в котором его вернул API, и значение.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct MoneyRepr {
+    currency: String,
+    value: Decimal,
+}
+
+impl FromRaw<Option<api::MoneyValue>> for Option<MoneyRepr> {
+    fn from_raw(raw: &Option<api::MoneyValue>) -> Self {
+        raw.as_ref().map(|money| MoneyRepr {
+            currency: money.currency.clone(),
+            value: money.into(),
+        })
+    }
+
+    fn into_raw(self) -> Result<Option<api::MoneyValue>, String> {
+        self.map(|money| {
+            (money.currency, money.value)
+                .try_into()
+                .map_err(|error| format!("{error}"))
+        })
+        .transpose()
+    }
+}
+
+impl FromRaw<Option<tinkoff_invest_grpc::Timestamp>> for Option<DateTime<Utc>> {
+    fn from_raw(raw: &Option<tinkoff_invest_grpc::Timestamp>) -> Self {
+        raw.as_ref().and_then(grpc_timestamp_to_chrono_timestamp)
+    }
+
+    fn into_raw(self) -> Result<Option<tinkoff_invest_grpc::Timestamp>, String> {
+        Ok(self.map(chrono_timestamp_to_grpc_timestamp))
+    }
+}
+
+/// Поля, которые SDK отдаёт как даты: время суток не сохраняется.
+impl FromRaw<Option<tinkoff_invest_grpc::Timestamp>> for Option<NaiveDate> {
+    fn from_raw(raw: &Option<tinkoff_invest_grpc::Timestamp>) -> Self {
+        raw.as_ref()
+            .and_then(grpc_timestamp_to_chrono_timestamp)
+            .map(|t| t.date_naive())
+    }
+
+    fn into_raw(self) -> Result<Option<tinkoff_invest_grpc::Timestamp>, String> {
+        Ok(self.map(|date| chrono_timestamp_to_grpc_timestamp(date.to_utc())))
+    }
+}
+
+/// Вариант перечисления: имя или число, если значение неизвестно SDK.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum EnumRepr {
+    Name(String),
+    Code(i32),
+}
+
+/// Serialize и Deserialize для обёртки над сообщением proto.
+/// Перечисляются поля сообщения и их представление, см. [`FromRaw`].
+macro_rules! proto_message_serde {
+    (
+        $wrapper:ident . $inner:tt : $($api:ident)::+ {
+            $($field:ident: $repr:ty),* $(,)?
+        }
+    ) => {
+        impl serde::Serialize for $wrapper {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use $crate::serialization::FromRaw;
+
+                #[derive(serde::Serialize)]
+                struct Repr {
+                    $($field: $repr,)*
+                }
+
+                Repr {
+                    $($field: FromRaw::from_raw(&self.$inner.$field),)*
+                }
+                .serialize(serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $wrapper {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                use $crate::serialization::FromRaw;
+                use serde::de::Error;
+
+                #[derive(serde::Deserialize)]
+                struct Repr {
+                    $($field: $repr,)*
+                }
+
+                let repr = Repr::deserialize(deserializer)?;
+                // Представление может перечислять не все поля сообщения
+                #[allow(clippy::needless_update)]
+                let raw = $($api)::+ {
+                    $($field: repr.$field.into_raw().map_err(D::Error::custom)?,)*
+                    ..Default::default()
+                };
+                Ok(raw.into())
+            }
+        }
+
+        impl $crate::serialization::FromRaw<Vec<$($api)::+>> for Vec<$wrapper> {
+            fn from_raw(raw: &Vec<$($api)::+>) -> Self {
+                raw.iter().cloned().map(Into::into).collect()
+            }
+
+            fn into_raw(self) -> Result<Vec<$($api)::+>, String> {
+                Ok(self.into_iter().map(|value| value.$inner).collect())
+            }
+        }
+    };
+}
+pub(crate) use proto_message_serde;
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Bond, CouponType, SecurityTradingStatus, TradingSchedule};
+    use tinkoff_invest_grpc::api;
+
+    fn timestamp(seconds: i64) -> Option<tinkoff_invest_grpc::Timestamp> {
+        Some(tinkoff_invest_grpc::Timestamp { seconds, nanos: 0 })
+    }
+
+    #[test]
+    fn bond_round_trip() {
+        let bond: Bond = api::Bond {
+            figi: "BBG00R05JT04".to_owned(),
+            nominal: Some(api::MoneyValue {
+                currency: "rub".to_owned(),
+                units: 1000,
+                nano: 0,
+            }),
+            klong: Some(api::Quotation {
+                units: 1,
+                nano: 500_000_000,
+            }),
+            maturity_date: timestamp(1_700_000_000 - 1_700_000_000 % 86_400),
+            trading_status: api::SecurityTradingStatus::NormalTrading as i32,
+            ..Default::default()
+        }
+        .into();
+
+        let json = serde_json::to_value(&bond).unwrap();
+        assert_eq!(json["figi"], "BBG00R05JT04");
+        assert_eq!(json["nominal"]["currency"], "rub");
+        assert_eq!(json["nominal"]["value"], "1000");
+        assert_eq!(json["klong"], "1.5");
+        assert_eq!(json["maturity_date"], "2023-11-14T00:00:00Z");
+        assert_eq!(json["trading_status"], "NormalTrading");
+
+        let bond: Bond = serde_json::from_value(json).unwrap();
+        assert_eq!(bond.figi().as_str(), "BBG00R05JT04");
+        assert_eq!(bond.nominal().unwrap().value(), 1000.into());
+        assert_eq!(bond.trading_status(), SecurityTradingStatus::NormalTrading);
+        assert_eq!(
+            bond.maturity_date().unwrap().to_string(),
+            "2023-11-14 00:00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_enum_values_and_nested_messages() {
+        assert_eq!(
+            serde_json::to_string(&CouponType::Unknown(42)).unwrap(),
+            "42"
+        );
+        assert_eq!(
+            serde_json::from_str::<CouponType>("\"Fix\"").unwrap(),
+            CouponType::Fix
+        );
+        assert_eq!(
+            serde_json::from_str::<CouponType>("42").unwrap(),
+            CouponType::Unknown(42)
+        );
+        assert!(serde_json::from_str::<CouponType>("\"Bogus\"").is_err());
+
+        let schedule: TradingSchedule = api::TradingSchedule {
+            exchange: "MOEX".to_owned(),
+            days: vec![api::TradingDay {
+                date: timestamp(1_672_531_200),
+                start_time: timestamp(1_672_556_400),
+                ..Default::default()
+            }],
+        }
+        .into();
+        let json = serde_json::to_string(&schedule).unwrap();
+        assert!(json.contains("\"start_time\":\"2023-01-01T07:00:00Z\""));
+        let schedule: TradingSchedule = serde_json::from_str(&json).unwrap();
+        assert_eq!(schedule.days().len(), 1);
+        assert_eq!(schedule.days()[0].date().unwrap().to_string(), "2023-01-01");
+    }
+}
@@ -1,41 +1,30 @@
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 
-use chrono::NaiveDate;
-use prost_types::Timestamp;
+use chrono::Duration;
+use tinkoff_invest_grpc::Timestamp;
 
-use crate::types;
+use crate::types::{self, TimestampBound};
 
-
-pub(crate) fn date_range_to_timestamp_pair(
-    range: impl RangeBounds<NaiveDate>,
+/// Первый и последний момент, входящие в интервал: конец даты `b` в `..=b` — это последняя наносекунда суток.
+pub(crate) fn date_range_to_timestamp_pair<T: TimestampBound>(
+    range: impl RangeBounds<T>,
 ) -> (Option<Timestamp>, Option<Timestamp>) {
     let start = match range.start_bound() {
-        std::ops::Bound::Included(date) => Some(types::chrono_timestamp_to_grpc_timestamp(
-            date.and_hms_opt(0, 0, 0)
-                .expect("Invalid hour/minute/second"),
-        )),
-        std::ops::Bound::Excluded(date) => Some(types::chrono_timestamp_to_grpc_timestamp(
-            date.and_hms_opt(0, 0, 0)
-                .expect("Invalid hour/minute/second")
-                - chrono::Duration::days(1),
-        )),
-        std::ops::Bound::Unbounded => None,
+        Bound::Included(bound) => Some(bound.to_utc()),
+        Bound::Excluded(bound) => Some(bound.after()),
+        Bound::Unbounded => None,
     };
 
     let end = match range.end_bound() {
-        std::ops::Bound::Included(date) => Some(types::chrono_timestamp_to_grpc_timestamp(
-            date.and_hms_opt(0, 0, 0)
-                .expect("Invalid hour/minute/second"),
-        )),
-        std::ops::Bound::Excluded(date) => Some(types::chrono_timestamp_to_grpc_timestamp(
-            date.and_hms_opt(0, 0, 0)
-                .expect("Invalid hour/minute/second")
-                + chrono::Duration::days(1),
-        )),
-        std::ops::Bound::Unbounded => None,
+        Bound::Included(bound) => Some(bound.after() - Duration::nanoseconds(1)),
+        Bound::Excluded(bound) => Some(bound.to_utc() - Duration::nanoseconds(1)),
+        Bound::Unbounded => None,
     };
 
-    (start, end)
+    (
+        start.map(types::chrono_timestamp_to_grpc_timestamp),
+        end.map(types::chrono_timestamp_to_grpc_timestamp),
+    )
 }
 
 pub(crate) trait EasyConvert<T> {
@@ -46,4 +35,62 @@ impl<TTarget, TSelf: Into<TTarget>> EasyConvert<Vec<TTarget>> for Vec<TSelf> {
     fn convert(self) -> Vec<TTarget> {
         self.into_iter().map(Into::into).collect()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+    use super::date_range_to_timestamp_pair;
+    use crate::types::{grpc_timestamp_to_chrono_timestamp, TimestampBound};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 3, day).unwrap()
+    }
+
+    fn midnight(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 3, day, 0, 0, 0).unwrap()
+    }
+
+    fn pair<T: TimestampBound>(
+        range: (Bound<T>, Bound<T>),
+    ) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let (start, end) = date_range_to_timestamp_pair(range);
+        let convert = |ts: Option<_>| ts.as_ref().and_then(grpc_timestamp_to_chrono_timestamp);
+        (convert(start), convert(end))
+    }
+
+    #[test]
+    fn date_bounds_cover_whole_days() {
+        let nanosecond = Duration::nanoseconds(1);
+        assert_eq!(
+            pair((Bound::Included(date(1)), Bound::Included(date(3)))),
+            (Some(midnight(1)), Some(midnight(4) - nanosecond))
+        );
+        assert_eq!(
+            pair((Bound::Excluded(date(1)), Bound::Excluded(date(3)))),
+            (Some(midnight(2)), Some(midnight(3) - nanosecond))
+        );
+    }
+
+    #[test]
+    fn time_bounds_move_by_nanosecond() {
+        let nanosecond = Duration::nanoseconds(1);
+        let time = midnight(2) + Duration::hours(10);
+        assert_eq!(
+            pair((Bound::Included(time), Bound::Included(time))),
+            (Some(time), Some(time))
+        );
+        assert_eq!(
+            pair((Bound::Excluded(time), Bound::Excluded(time))),
+            (Some(time + nanosecond), Some(time - nanosecond))
+        );
+    }
+
+    #[test]
+    fn full_range_with_explicit_bound_type() {
+        assert_eq!(date_range_to_timestamp_pair::<NaiveDate>(..), (None, None));
+    }
+}
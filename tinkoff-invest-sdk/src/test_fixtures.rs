@@ -0,0 +1,57 @@
+//! Общие данные для тестов: время в марте 2023 года, торговые дни и свечи.
+
+use chrono::{DateTime, TimeZone, Utc};
+use tinkoff_invest_grpc::api;
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use crate::trading_calendar::TradingCalendar;
+use crate::types::{Candle, TradingDay};
+
+/// Время в UTC. 2023-03-03 — пятница, 4 и 5 — выходные.
+pub(crate) fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2023, 3, day, hour, minute, 0).unwrap()
+}
+
+pub(crate) fn ts(time: DateTime<Utc>) -> Option<tinkoff_invest_grpc::Timestamp> {
+    Some(tinkoff_invest_grpc::Timestamp {
+        seconds: time.timestamp(),
+        nanos: 0,
+    })
+}
+
+/// Торговый день с основной сессией `start..end`, время — часы и минуты UTC.
+/// Остальные поля можно дополнить через `..trading_day(..)`.
+pub(crate) fn trading_day(day: u32, start: (u32, u32), end: (u32, u32)) -> api::TradingDay {
+    api::TradingDay {
+        date: ts(at(day, 0, 0)),
+        is_trading_day: true,
+        start_time: ts(at(day, start.0, start.1)),
+        end_time: ts(at(day, end.0, end.1)),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn non_trading_day(day: u32) -> api::TradingDay {
+    api::TradingDay {
+        date: ts(at(day, 0, 0)),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn calendar(days: impl IntoIterator<Item = api::TradingDay>) -> TradingCalendar {
+    let days: Vec<TradingDay> = days.into_iter().map(TradingDay::from).collect();
+    TradingCalendar::new(&days)
+}
+
+/// Сформированная свеча: open и close равны `price`, high и low отстоят от неё на единицу.
+pub(crate) fn candle(time: DateTime<Utc>, price: i64) -> Candle {
+    Candle {
+        open: Decimal::from(price),
+        high: Decimal::from(price + 1),
+        low: Decimal::from(price - 1),
+        close: Decimal::from(price),
+        volume: 1,
+        time,
+        is_complete: true,
+    }
+}
@@ -0,0 +1,351 @@
+//! Торговый календарь биржи: фаза торгов в заданный момент, ближайшие открытие и закрытие,
+//! торговые дни в интервале.
+//!
+//! Строится по расписанию из [`InstrumentsClient::trading_schedules`](crate::instruments::InstrumentsClient::trading_schedules)
+//! или сразу через [`InstrumentsClient::trading_calendar`](crate::instruments::InstrumentsClient::trading_calendar).
+//! Время везде в UTC, как в расписании, а даты торговых дней — московские.
+
+use std::ops::{Bound, RangeBounds};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::types::{MoscowTime, TradingDay, TradingSchedule};
+
+/// Фаза торговой сессии.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SessionPhase {
+    /// Премаркет.
+    Premarket,
+    /// Аукцион открытия.
+    OpeningAuction,
+    /// Основная сессия.
+    Main,
+    /// Аукцион закрытия.
+    ClosingAuction,
+    /// Клиринг: торги приостановлены.
+    Clearing,
+    /// Вечерняя сессия, включая её аукцион открытия.
+    Evening,
+    /// Торгов нет.
+    Closed,
+}
+
+impl SessionPhase {
+    /// Можно ли в эту фазу заключать сделки.
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        !matches!(self, Self::Clearing | Self::Closed)
+    }
+}
+
+/// Непрерывный интервал торгов `[start, end)`: соседние фазы объединены, клиринг разрывает сессию.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Session {
+    #[inline]
+    pub fn contains(&self, ts: DateTime<Utc>) -> bool {
+        self.start <= ts && ts < self.end
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    phase: SessionPhase,
+}
+
+#[derive(Debug, Clone)]
+struct Day {
+    date: NaiveDate,
+    is_trading_day: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TradingCalendar {
+    days: Vec<Day>,
+    /// Фазы всех дней без пересечений, по возрастанию времени.
+    segments: Vec<Segment>,
+    sessions: Vec<Session>,
+}
+
+impl From<&TradingSchedule> for TradingCalendar {
+    fn from(schedule: &TradingSchedule) -> Self {
+        Self::new(schedule.days())
+    }
+}
+
+impl TradingCalendar {
+    /// Календарь по дням расписания одной биржи. Дни без даты пропускаются,
+    /// из повторяющихся дат остаётся первая.
+    pub fn new<'a>(days: impl IntoIterator<Item = &'a TradingDay>) -> Self {
+        let mut calendar = Self::default();
+        for day in days {
+            let date = match day.date() {
+                Some(date) => date,
+                None => continue,
+            };
+            calendar.days.push(Day {
+                date,
+                is_trading_day: day.is_trading_day(),
+            });
+            if day.is_trading_day() {
+                calendar.segments.extend(day_segments(day));
+            }
+        }
+        calendar.days.sort_by_key(|day| day.date);
+        calendar.days.dedup_by_key(|day| day.date);
+        calendar.segments.sort_by_key(|segment| segment.start);
+        calendar.sessions = merge_sessions(&calendar.segments);
+        calendar
+    }
+
+    /// Фаза торгов в момент `ts`.
+    /// `None`, если московская дата момента вне дат календаря.
+    pub fn session_phase_at(&self, ts: DateTime<Utc>) -> Option<SessionPhase> {
+        self.is_trading_day(ts.moscow_date())?;
+        let index = self.segments.partition_point(|segment| segment.start <= ts);
+        let phase = index
+            .checked_sub(1)
+            .map(|index| self.segments[index])
+            .filter(|segment| ts < segment.end)
+            .map_or(SessionPhase::Closed, |segment| segment.phase);
+        Some(phase)
+    }
+
+    /// Идут ли торги в момент `ts`. Вне дат календаря — `false`.
+    pub fn is_open_at(&self, ts: DateTime<Utc>) -> bool {
+        self.session_at(ts).is_some()
+    }
+
+    /// Торговая сессия, которая идёт в момент `ts`.
+    pub fn session_at(&self, ts: DateTime<Utc>) -> Option<Session> {
+        let index = self.sessions.partition_point(|session| session.start <= ts);
+        let session = self.sessions[index.checked_sub(1)?];
+        session.contains(ts).then_some(session)
+    }
+
+    /// Ближайшее начало торгов строго после `ts`.
+    pub fn next_open(&self, ts: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let index = self.sessions.partition_point(|session| session.start <= ts);
+        self.sessions.get(index).map(|session| session.start)
+    }
+
+    /// Ближайшее окончание торгов строго после `ts`: конец текущей сессии, если торги идут,
+    /// иначе конец следующей.
+    pub fn next_close(&self, ts: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let index = self.sessions.partition_point(|session| session.end <= ts);
+        self.sessions.get(index).map(|session| session.end)
+    }
+
+    /// Все торговые сессии календаря по возрастанию времени.
+    #[inline]
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    /// Является ли дата торговым днём. `None`, если даты нет в календаре.
+    pub fn is_trading_day(&self, date: NaiveDate) -> Option<bool> {
+        let index = self.days.binary_search_by_key(&date, |day| day.date).ok()?;
+        Some(self.days[index].is_trading_day)
+    }
+
+    /// Торговые дни календаря из интервала.
+    pub fn trading_days(
+        &self,
+        range: impl RangeBounds<NaiveDate>,
+    ) -> impl Iterator<Item = NaiveDate> + '_ {
+        let start = match range.start_bound() {
+            Bound::Included(date) => Bound::Included(*date),
+            Bound::Excluded(date) => Bound::Excluded(*date),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(date) => Bound::Included(*date),
+            Bound::Excluded(date) => Bound::Excluded(*date),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        self.days
+            .iter()
+            .filter(move |day| day.is_trading_day && (start, end).contains(&day.date))
+            .map(|day| day.date)
+    }
+
+    /// Число торговых дней в `[from, to)`.
+    pub fn trading_days_between(&self, from: NaiveDate, to: NaiveDate) -> usize {
+        self.trading_days(from..to).count()
+    }
+}
+
+/// Разбить день на непересекающиеся фазы.
+/// Если интервалы фаз пересекаются, побеждает более узкая: клиринг внутри основной сессии,
+/// аукционы на её границах.
+fn day_segments(day: &TradingDay) -> Vec<Segment> {
+    let mut intervals = Vec::new();
+    let mut push = |start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>, phase| {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start < end {
+                intervals.push(Segment { start, end, phase });
+            }
+        }
+    };
+
+    let main = day.trading_time();
+    let main_start = main.as_ref().map(|range| *range.start());
+    let main_end = main.as_ref().map(|range| *range.end());
+    let evening = day.evening_trading_time();
+    let evening_start = day
+        .evening_opening_auction_start_time()
+        .or_else(|| evening.as_ref().map(|range| *range.start()));
+
+    // Порядок — приоритет при пересечении
+    push(
+        day.clearing_time().map(|r| *r.start()),
+        day.clearing_time().map(|r| *r.end()),
+        SessionPhase::Clearing,
+    );
+    push(
+        day.opening_auction_start_time(),
+        main_start,
+        SessionPhase::OpeningAuction,
+    );
+    push(
+        main_end,
+        day.closing_auction_end_time(),
+        SessionPhase::ClosingAuction,
+    );
+    push(
+        day.premarket_time().map(|r| *r.start()),
+        day.premarket_time().map(|r| *r.end()),
+        SessionPhase::Premarket,
+    );
+    push(
+        evening_start,
+        evening.as_ref().map(|range| *range.end()),
+        SessionPhase::Evening,
+    );
+    push(main_start, main_end, SessionPhase::Main);
+
+    let mut bounds: Vec<DateTime<Utc>> = intervals
+        .iter()
+        .flat_map(|interval| [interval.start, interval.end])
+        .collect();
+    bounds.sort();
+    bounds.dedup();
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for window in bounds.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let phase = match intervals
+            .iter()
+            .find(|interval| interval.start <= start && start < interval.end)
+        {
+            Some(interval) => interval.phase,
+            None => continue,
+        };
+        match segments.last_mut() {
+            Some(last) if last.end == start && last.phase == phase => last.end = end,
+            _ => segments.push(Segment { start, end, phase }),
+        }
+    }
+    segments
+}
+
+fn merge_sessions(segments: &[Segment]) -> Vec<Session> {
+    let mut sessions: Vec<Session> = Vec::new();
+    for segment in segments.iter().filter(|segment| segment.phase.is_open()) {
+        match sessions.last_mut() {
+            Some(last) if segment.start <= last.end => last.end = last.end.max(segment.end),
+            _ => sessions.push(Session {
+                start: segment.start,
+                end: segment.end,
+            }),
+        }
+    }
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tinkoff_invest_grpc::api;
+
+    use crate::test_fixtures::{at, calendar, non_trading_day, trading_day, ts};
+
+    /// Срочный рынок Мосбиржи в UTC: основная сессия с дневным клирингом и вечерняя.
+    fn futures_day(day: u32) -> api::TradingDay {
+        api::TradingDay {
+            opening_auction_start_time: ts(at(day, 6, 50)),
+            closing_auction_end_time: ts(at(day, 15, 50)),
+            clearing_start_time: ts(at(day, 11, 0)),
+            clearing_end_time: ts(at(day, 11, 5)),
+            evening_start_time: ts(at(day, 16, 5)),
+            evening_end_time: ts(at(day, 20, 50)),
+            ..trading_day(day, (7, 0), (15, 45))
+        }
+    }
+
+    fn futures_calendar() -> TradingCalendar {
+        // Пятница, выходные, понедельник
+        calendar([
+            futures_day(3),
+            non_trading_day(4),
+            non_trading_day(5),
+            futures_day(6),
+        ])
+    }
+
+    #[test]
+    fn resolves_session_phases() {
+        let calendar = futures_calendar();
+        let phase = |day, hour, minute| calendar.session_phase_at(at(day, hour, minute));
+        assert_eq!(phase(3, 6, 55), Some(SessionPhase::OpeningAuction));
+        assert_eq!(phase(3, 7, 0), Some(SessionPhase::Main));
+        assert_eq!(phase(3, 11, 2), Some(SessionPhase::Clearing));
+        assert_eq!(phase(3, 15, 47), Some(SessionPhase::ClosingAuction));
+        assert_eq!(phase(3, 16, 0), Some(SessionPhase::Closed));
+        assert_eq!(phase(3, 18, 0), Some(SessionPhase::Evening));
+        assert_eq!(phase(4, 12, 0), Some(SessionPhase::Closed));
+        assert_eq!(phase(7, 12, 0), None);
+        // После 21:00 UTC в Москве уже следующий день
+        assert_eq!(phase(2, 22, 0), Some(SessionPhase::Closed));
+        assert_eq!(phase(6, 21, 30), None);
+    }
+
+    #[test]
+    fn finds_next_open_and_close() {
+        let calendar = futures_calendar();
+        assert!(calendar.is_open_at(at(3, 10, 0)));
+        assert!(!calendar.is_open_at(at(3, 11, 0)));
+        assert_eq!(calendar.next_close(at(3, 10, 0)), Some(at(3, 11, 0)));
+        assert_eq!(calendar.next_open(at(3, 10, 0)), Some(at(3, 11, 5)));
+        assert_eq!(calendar.next_close(at(3, 12, 0)), Some(at(3, 15, 50)));
+        assert_eq!(calendar.next_open(at(3, 21, 0)), Some(at(6, 6, 50)));
+        assert_eq!(calendar.next_open(at(6, 16, 10)), None);
+        assert_eq!(
+            calendar.session_at(at(3, 19, 0)),
+            Some(Session {
+                start: at(3, 16, 5),
+                end: at(3, 20, 50)
+            })
+        );
+    }
+
+    #[test]
+    fn counts_trading_days() {
+        let calendar = futures_calendar();
+        let date = |day| NaiveDate::from_ymd_opt(2023, 3, day).unwrap();
+        assert_eq!(calendar.is_trading_day(date(4)), Some(false));
+        assert_eq!(calendar.is_trading_day(date(1)), None);
+        assert_eq!(calendar.trading_days_between(date(3), date(6)), 1);
+        assert_eq!(
+            calendar.trading_days(..).collect::<Vec<_>>(),
+            vec![date(3), date(6)]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use tinkoff_invest_grpc::decimal::rust_decimal::Decimal;
+
+use super::Bond;
+
+/// Цена облигации в процентах от номинала — в таком виде её отдают котировки и заявки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct BondPrice(Decimal);
+
+impl BondPrice {
+    #[inline]
+    pub fn from_percent(percent: Decimal) -> Self {
+        Self(percent)
+    }
+
+    /// Цена в процентах от номинала.
+    #[inline]
+    pub fn percent(&self) -> Decimal {
+        self.0
+    }
+}
+
+impl fmt::Display for BondPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// Учитывать ли НКД при переводе цены в деньги.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Aci {
+    /// Чистая цена, без НКД.
+    Excluded,
+    /// Грязная цена: с текущим НКД из [`Bond::aci_value`].
+    Included,
+}
+
+/// Перевод цены из процентов от номинала в деньги и обратно.
+///
+/// Суммы указаны в валюте номинала. Все методы возвращают `None`, если номинал неизвестен.
+impl Bond {
+    /// Стоимость одной облигации.
+    pub fn money_per_bond(&self, price: BondPrice, aci: Aci) -> Option<Decimal> {
+        let clean = price.percent() * self.nominal_value()? / Decimal::ONE_HUNDRED;
+        Some(clean + self.aci_per_bond(aci))
+    }
+
+    /// Стоимость одного лота.
+    pub fn money_per_lot(&self, price: BondPrice, aci: Aci) -> Option<Decimal> {
+        Some(self.money_per_bond(price, aci)? * Decimal::from(self.lot()))
+    }
+
+    /// Цена по стоимости одной облигации.
+    pub fn price_from_money_per_bond(&self, money: Decimal, aci: Aci) -> Option<BondPrice> {
+        let nominal = self.nominal_value()?;
+        if nominal.is_zero() {
+            return None;
+        }
+        let clean = money - self.aci_per_bond(aci);
+        Some(BondPrice::from_percent(
+            clean * Decimal::ONE_HUNDRED / nominal,
+        ))
+    }
+
+    /// Цена по стоимости одного лота.
+    pub fn price_from_money_per_lot(&self, money: Decimal, aci: Aci) -> Option<BondPrice> {
+        if self.lot() == 0 {
+            return None;
+        }
+        self.price_from_money_per_bond(money / Decimal::from(self.lot()), aci)
+    }
+
+    fn nominal_value(&self) -> Option<Decimal> {
+        self.nominal().map(|n| n.value())
+    }
+
+    fn aci_per_bond(&self, aci: Aci) -> Decimal {
+        match aci {
+            Aci::Excluded => Decimal::ZERO,
+            Aci::Included => self.aci_value().map(|a| a.value()).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tinkoff_invest_grpc::api;
+
+    fn money(units: i64, nano: i32) -> Option<api::MoneyValue> {
+        Some(api::MoneyValue {
+            currency: "rub".to_owned(),
+            units,
+            nano,
+        })
+    }
+
+    #[test]
+    fn converts_percent_to_money_and_back() {
+        let bond: Bond = api::Bond {
+            lot: 10,
+            nominal: money(1000, 0),
+            aci_value: money(12, 340_000_000),
+            ..Default::default()
+        }
+        .into();
+        let price = BondPrice::from_percent(Decimal::new(9850, 2));
+
+        assert_eq!(
+            bond.money_per_bond(price, Aci::Excluded),
+            Some(Decimal::from(985))
+        );
+        assert_eq!(
+            bond.money_per_bond(price, Aci::Included),
+            Some(Decimal::new(99734, 2))
+        );
+        assert_eq!(
+            bond.money_per_lot(price, Aci::Included),
+            Some(Decimal::new(997340, 2))
+        );
+        assert_eq!(
+            bond.price_from_money_per_lot(Decimal::new(997340, 2), Aci::Included),
+            Some(price)
+        );
+    }
+}
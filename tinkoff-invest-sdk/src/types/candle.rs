@@ -0,0 +1,116 @@
+use chrono::{DateTime, Duration, Utc};
+use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
+
+use super::grpc_timestamp_to_chrono_timestamp;
+use crate::proto_enum;
+
+proto_enum! {
+    pub enum CandleInterval: CandleInterval {
+        /// Интервал не определён
+        Unspecified,
+        /// 1 минута
+        OneMinute = CandleInterval1Min,
+        /// 5 минут
+        FiveMinutes = CandleInterval5Min,
+        /// 15 минут
+        FifteenMinutes = CandleInterval15Min,
+        /// 1 час
+        Hour,
+        /// 1 день
+        Day,
+    }
+}
+
+impl CandleInterval {
+    /// Длительность одной свечи.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::OneMinute => Some(Duration::minutes(1)),
+            Self::FiveMinutes => Some(Duration::minutes(5)),
+            Self::FifteenMinutes => Some(Duration::minutes(15)),
+            Self::Hour => Some(Duration::hours(1)),
+            Self::Day => Some(Duration::days(1)),
+            _ => None,
+        }
+    }
+
+    /// Наибольший интервал времени, за который API отдаёт свечи одним запросом.
+    pub fn max_request_span(&self) -> Option<Duration> {
+        match self {
+            Self::OneMinute | Self::FiveMinutes | Self::FifteenMinutes => Some(Duration::days(1)),
+            Self::Hour => Some(Duration::weeks(1)),
+            Self::Day => Some(Duration::days(365)),
+            _ => None,
+        }
+    }
+}
+
+/// Свеча.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Candle {
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// Объём торгов в лотах.
+    pub volume: i64,
+    /// Время начала интервала свечи.
+    pub time: DateTime<Utc>,
+    /// Сформирована ли свеча: интервал закончился.
+    pub is_complete: bool,
+}
+
+impl Candle {
+    /// `None`, если API не вернул время или одну из цен свечи.
+    pub(crate) fn from_api(candle: &api::HistoricCandle) -> Option<Self> {
+        let price = |price: &Option<api::Quotation>| price.as_ref().map(Decimal::from);
+        Some(Self {
+            open: price(&candle.open)?,
+            high: price(&candle.high)?,
+            low: price(&candle.low)?,
+            close: price(&candle.close)?,
+            volume: candle.volume,
+            time: candle
+                .time
+                .as_ref()
+                .and_then(grpc_timestamp_to_chrono_timestamp)?,
+            is_complete: candle.is_complete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_historic_candle() {
+        let quotation = |units| Some(api::Quotation { units, nano: 0 });
+        let mut candle = api::HistoricCandle {
+            open: quotation(10),
+            high: quotation(12),
+            low: quotation(9),
+            close: quotation(11),
+            volume: 150,
+            is_complete: true,
+            ..Default::default()
+        };
+        candle.time = Some(tinkoff_invest_grpc::Timestamp {
+            seconds: 1_672_531_200,
+            nanos: 0,
+        });
+        let converted = Candle::from_api(&candle).unwrap();
+        assert_eq!(converted.high, Decimal::from(12));
+        assert_eq!(converted.time.to_rfc3339(), "2023-01-01T00:00:00+00:00");
+        candle.low = None;
+        assert_eq!(Candle::from_api(&candle), None);
+        candle.low = quotation(9);
+        candle.time = None;
+        assert_eq!(Candle::from_api(&candle), None);
+        assert_eq!(
+            CandleInterval::from(api::CandleInterval::Hour as i32).max_request_span(),
+            Some(Duration::weeks(1))
+        );
+    }
+}
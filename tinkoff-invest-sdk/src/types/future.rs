@@ -0,0 +1,370 @@
+use chrono::{DateTime, Utc};
+use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
+
+use super::{
+    grpc_timestamp_to_chrono_timestamp, ClassCode, Figi, InstrumentUid, Long, MoneyValue,
+    PositionUid, RealExchange, SecurityTradingStatus, Short, Ticker,
+};
+
+#[cfg(feature = "serde")]
+use crate::serialization::{proto_message_serde, MoneyRepr};
+
+#[derive(Debug, Clone)]
+pub struct Future(api::Future);
+
+impl From<api::Future> for Future {
+    fn from(future: api::Future) -> Self {
+        Self(future)
+    }
+}
+
+impl Future {
+    #[inline]
+    pub fn figi(&self) -> &Figi {
+        Figi::from_ref(&self.0.figi)
+    }
+
+    #[inline]
+    pub fn ticker(&self) -> &Ticker {
+        Ticker::from_ref(&self.0.ticker)
+    }
+
+    #[inline]
+    pub fn class_code(&self) -> &ClassCode {
+        ClassCode::from_ref(&self.0.class_code)
+    }
+
+    #[inline]
+    pub fn lot(&self) -> i32 {
+        self.0.lot
+    }
+
+    #[inline]
+    pub fn currency(&self) -> &str {
+        &self.0.currency
+    }
+
+    #[inline]
+    pub fn short(&self) -> Option<Short> {
+        if self.0.short_enabled_flag {
+            Some(Short {
+                kshort: self.0.kshort.as_ref().map(Into::into)?,
+                dshort: self.0.dshort.as_ref().map(Into::into)?,
+                dshort_min: self.0.dshort_min.as_ref().map(Into::into)?,
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn long(&self) -> Option<Long> {
+        Some(Long {
+            klong: self.0.klong.as_ref().map(Into::into)?,
+            dlong: self.0.dlong.as_ref().map(Into::into)?,
+            dlong_min: self.0.dlong_min.as_ref().map(Into::into)?,
+        })
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    #[inline]
+    pub fn exchange(&self) -> &str {
+        &self.0.exchange
+    }
+
+    /// Дата начала обращения контракта.
+    #[inline]
+    pub fn first_trade_date(&self) -> Option<DateTime<Utc>> {
+        self.0
+            .first_trade_date
+            .as_ref()
+            .and_then(grpc_timestamp_to_chrono_timestamp)
+    }
+
+    /// Дата по которую осуществляется обращение контракта.
+    #[inline]
+    pub fn last_trade_date(&self) -> Option<DateTime<Utc>> {
+        self.0
+            .last_trade_date
+            .as_ref()
+            .and_then(grpc_timestamp_to_chrono_timestamp)
+    }
+
+    /// Тип фьючерса: физические поставки или расчётный.
+    #[inline]
+    pub fn futures_type(&self) -> &str {
+        &self.0.futures_type
+    }
+
+    /// Тип актива: валюта, товар, индекс или ценная бумага.
+    #[inline]
+    pub fn asset_type(&self) -> &str {
+        &self.0.asset_type
+    }
+
+    /// Основной актив.
+    #[inline]
+    pub fn basic_asset(&self) -> &str {
+        &self.0.basic_asset
+    }
+
+    /// Размер основного актива.
+    #[inline]
+    pub fn basic_asset_size(&self) -> Option<Decimal> {
+        self.0.basic_asset_size.as_ref().map(Into::into)
+    }
+
+    #[inline]
+    pub fn sector(&self) -> &str {
+        &self.0.sector
+    }
+
+    /// Дата истечения срока.
+    #[inline]
+    pub fn expiration_date(&self) -> Option<DateTime<Utc>> {
+        self.0
+            .expiration_date
+            .as_ref()
+            .and_then(grpc_timestamp_to_chrono_timestamp)
+    }
+
+    #[inline]
+    pub fn trading_status(&self) -> SecurityTradingStatus {
+        self.0.trading_status.into()
+    }
+
+    #[inline]
+    pub fn is_otc(&self) -> bool {
+        self.0.otc_flag
+    }
+
+    #[inline]
+    pub fn purchase_available(&self) -> bool {
+        self.0.buy_available_flag
+    }
+
+    #[inline]
+    pub fn sell_available_flag(&self) -> bool {
+        self.0.sell_available_flag
+    }
+
+    /// Шаг цены в пунктах.
+    #[inline]
+    pub fn min_price_increment(&self) -> Option<Decimal> {
+        self.0.min_price_increment.as_ref().map(Into::into)
+    }
+
+    #[inline]
+    pub fn api_trade_available(&self) -> bool {
+        self.0.api_trade_available_flag
+    }
+
+    #[inline]
+    pub fn uid(&self) -> &InstrumentUid {
+        InstrumentUid::from_ref(&self.0.uid)
+    }
+
+    #[inline]
+    pub fn real_exchange(&self) -> RealExchange {
+        self.0.real_exchange.into()
+    }
+
+    #[inline]
+    pub fn position_uid(&self) -> &PositionUid {
+        PositionUid::from_ref(&self.0.position_uid)
+    }
+
+    #[inline]
+    pub fn basic_asset_position_uid(&self) -> &PositionUid {
+        PositionUid::from_ref(&self.0.basic_asset_position_uid)
+    }
+
+    #[inline]
+    pub fn available_for_iis(&self) -> bool {
+        self.0.for_iis_flag
+    }
+
+    #[inline]
+    pub fn first_minute_candle_date(&self) -> Option<DateTime<Utc>> {
+        self.0
+            .first_1min_candle_date
+            .as_ref()
+            .and_then(grpc_timestamp_to_chrono_timestamp)
+    }
+
+    #[inline]
+    pub fn first_day_candle_date(&self) -> Option<DateTime<Utc>> {
+        self.0
+            .first_1day_candle_date
+            .as_ref()
+            .and_then(grpc_timestamp_to_chrono_timestamp)
+    }
+}
+
+/// Перевод цены фьючерса из пунктов в рубли и обратно.
+///
+/// Стоимость пункта берётся из [`FuturesMargin`], так как в самом инструменте её нет.
+/// Все методы возвращают `None`, если шаг цены или его стоимость неизвестны.
+impl Future {
+    /// Стоимость одного контракта в рублях по цене в пунктах.
+    pub fn points_to_money(&self, points: Decimal, margin: &FuturesMargin) -> Option<Decimal> {
+        Some(points * margin.point_value()?)
+    }
+
+    /// Цена в пунктах по стоимости одного контракта в рублях.
+    pub fn money_to_points(&self, money: Decimal, margin: &FuturesMargin) -> Option<Decimal> {
+        let point_value = margin.point_value()?;
+        if point_value.is_zero() {
+            return None;
+        }
+        Some(money / point_value)
+    }
+
+    /// Стоимость позиции в рублях: `quantity` лотов по цене `price` в пунктах.
+    /// Для коротких позиций количество отрицательное.
+    pub fn position_value(
+        &self,
+        price: Decimal,
+        quantity: i64,
+        margin: &FuturesMargin,
+    ) -> Option<Decimal> {
+        let contracts = Decimal::from(quantity) * Decimal::from(self.lot());
+        Some(self.points_to_money(price, margin)? * contracts)
+    }
+}
+
+/// Размер гарантийного обеспечения и стоимость шага цены фьючерса.
+#[repr(transparent)]
+#[derive(Debug, Clone)]
+pub struct FuturesMargin(api::GetFuturesMarginResponse);
+
+impl From<api::GetFuturesMarginResponse> for FuturesMargin {
+    #[inline(always)]
+    fn from(response: api::GetFuturesMarginResponse) -> Self {
+        Self(response)
+    }
+}
+
+impl FuturesMargin {
+    /// Гарантийное обеспечение при покупке.
+    #[inline(always)]
+    pub fn initial_margin_on_buy(&self) -> Option<MoneyValue<'_>> {
+        self.0.initial_margin_on_buy.as_ref().map(Into::into)
+    }
+
+    /// Гарантийное обеспечение при продаже.
+    #[inline(always)]
+    pub fn initial_margin_on_sell(&self) -> Option<MoneyValue<'_>> {
+        self.0.initial_margin_on_sell.as_ref().map(Into::into)
+    }
+
+    /// Шаг цены в пунктах.
+    #[inline(always)]
+    pub fn min_price_increment(&self) -> Option<Decimal> {
+        self.0.min_price_increment.as_ref().map(Into::into)
+    }
+
+    /// Стоимость шага цены в рублях.
+    #[inline(always)]
+    pub fn min_price_increment_amount(&self) -> Option<Decimal> {
+        self.0.min_price_increment_amount.as_ref().map(Into::into)
+    }
+
+    /// Стоимость одного пункта в рублях.
+    pub fn point_value(&self) -> Option<Decimal> {
+        let increment = self.min_price_increment()?;
+        if increment.is_zero() {
+            return None;
+        }
+        Some(self.min_price_increment_amount()? / increment)
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    Future.0: api::Future {
+        figi: String,
+        ticker: String,
+        class_code: String,
+        lot: i32,
+        currency: String,
+        klong: Option<Decimal>,
+        kshort: Option<Decimal>,
+        dlong: Option<Decimal>,
+        dshort: Option<Decimal>,
+        dlong_min: Option<Decimal>,
+        dshort_min: Option<Decimal>,
+        short_enabled_flag: bool,
+        name: String,
+        exchange: String,
+        first_trade_date: Option<DateTime<Utc>>,
+        last_trade_date: Option<DateTime<Utc>>,
+        futures_type: String,
+        asset_type: String,
+        basic_asset: String,
+        basic_asset_size: Option<Decimal>,
+        sector: String,
+        expiration_date: Option<DateTime<Utc>>,
+        trading_status: SecurityTradingStatus,
+        otc_flag: bool,
+        buy_available_flag: bool,
+        sell_available_flag: bool,
+        min_price_increment: Option<Decimal>,
+        api_trade_available_flag: bool,
+        uid: String,
+        real_exchange: RealExchange,
+        position_uid: String,
+        basic_asset_position_uid: String,
+        for_iis_flag: bool,
+        first_1min_candle_date: Option<DateTime<Utc>>,
+        first_1day_candle_date: Option<DateTime<Utc>>,
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    FuturesMargin.0: api::GetFuturesMarginResponse {
+        initial_margin_on_buy: Option<MoneyRepr>,
+        initial_margin_on_sell: Option<MoneyRepr>,
+        min_price_increment: Option<Decimal>,
+        min_price_increment_amount: Option<Decimal>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotation(units: i64, nano: i32) -> Option<api::Quotation> {
+        Some(api::Quotation { units, nano })
+    }
+
+    #[test]
+    fn converts_points_to_rubles() {
+        // Фьючерс на индекс РТС: шаг 10 пунктов стоит около 14.6 рубля
+        let future: Future = api::Future {
+            lot: 1,
+            ..Default::default()
+        }
+        .into();
+        let margin: FuturesMargin = api::GetFuturesMarginResponse {
+            min_price_increment: quotation(10, 0),
+            min_price_increment_amount: quotation(14, 600_000_000),
+            ..Default::default()
+        }
+        .into();
+
+        let price = Decimal::from(100_000);
+        let money = Decimal::from(146_000);
+        assert_eq!(future.points_to_money(price, &margin), Some(money));
+        assert_eq!(future.money_to_points(money, &margin), Some(price));
+        assert_eq!(
+            future.position_value(price, -3, &margin),
+            Some(Decimal::from(-438_000))
+        );
+    }
+}
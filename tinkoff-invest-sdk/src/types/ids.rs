@@ -0,0 +1,303 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident, $kind:literal, $validate:path) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(try_from = "String", into = "String")
+        )]
+        #[repr(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Проверить формат и создать идентификатор.
+            pub fn new(value: impl Into<String>) -> Result<Self, InvalidId> {
+                let value = value.into();
+                if $validate(&value) {
+                    Ok(Self(value))
+                } else {
+                    Err(InvalidId { kind: $kind, value })
+                }
+            }
+
+            /// Значение из ответа API: формат не проверяется.
+            #[inline]
+            #[allow(dead_code)] // нужен не каждому идентификатору
+            pub(crate) fn from_api(value: String) -> Self {
+                Self(value)
+            }
+
+            /// Десериализация без проверки формата, как у [`Self::from_api`]: для полей типов,
+            /// которые строятся из ответов API.
+            #[cfg(feature = "serde")]
+            #[allow(dead_code)] // нужен не каждому идентификатору
+            pub(crate) fn deserialize_unchecked<'de, D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                <String as serde::Deserialize>::deserialize(deserializer).map(Self)
+            }
+
+            /// Ссылка на значение из ответа API: формат не проверяется.
+            #[inline]
+            pub(crate) fn from_ref(value: &String) -> &Self {
+                // SAFETY: #[repr(transparent)] над String.
+                unsafe { &*(value as *const String as *const Self) }
+            }
+
+            #[inline]
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            #[inline]
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = InvalidId;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::new(value)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = InvalidId;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = InvalidId;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+id_type!(
+    /// FIGI-идентификатор инструмента. Контрольная цифра проверяется только у FIGI
+    /// с префиксом `BBG`: у внутренних идентификаторов брокера (`TCS...`) её нет.
+    Figi,
+    "FIGI",
+    is_valid_figi
+);
+id_type!(
+    /// Уникальный идентификатор инструмента (UUID).
+    InstrumentUid,
+    "instrument uid",
+    is_valid_uuid
+);
+id_type!(
+    /// Уникальный идентификатор позиции (UUID).
+    PositionUid,
+    "position uid",
+    is_valid_uuid
+);
+id_type!(
+    /// Идентификатор счёта.
+    AccountId,
+    "account id",
+    is_valid_code
+);
+id_type!(
+    /// Тикер инструмента.
+    Ticker,
+    "ticker",
+    is_valid_code
+);
+id_type!(
+    /// Код режима торгов (class code).
+    ClassCode,
+    "class code",
+    is_valid_code
+);
+
+fn is_valid_figi(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    if bytes.len() != 12
+        || !bytes
+            .iter()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+    {
+        return false;
+    }
+    if !value.starts_with("BBG") {
+        return true;
+    }
+    let sum: u32 = bytes[..11]
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let value = match c {
+                b'0'..=b'9' => u32::from(c - b'0'),
+                _ => u32::from(c - b'A') + 10,
+            };
+            let value = if i % 2 == 1 { value * 2 } else { value };
+            value / 10 + value % 10
+        })
+        .sum();
+    let check = (10 - sum % 10) % 10;
+    u32::from(bytes[11].wrapping_sub(b'0')) == check
+}
+
+/// UUID в записи с дефисами: `8-4-4-4-12` шестнадцатеричных цифр.
+fn is_valid_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_valid_code(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|c| c.is_ascii_graphic())
+}
+
+/// Идентификатор инструмента в запросах, которые принимают и FIGI, и uid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InstrumentId {
+    Figi(Figi),
+    Uid(InstrumentUid),
+}
+
+impl InstrumentId {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Figi(figi) => figi.as_str(),
+            Self::Uid(uid) => uid.as_str(),
+        }
+    }
+}
+
+impl From<Figi> for InstrumentId {
+    fn from(figi: Figi) -> Self {
+        Self::Figi(figi)
+    }
+}
+
+impl From<InstrumentUid> for InstrumentId {
+    fn from(uid: InstrumentUid) -> Self {
+        Self::Uid(uid)
+    }
+}
+
+impl FromStr for InstrumentId {
+    type Err = InvalidId;
+
+    /// uid, если строка в формате UUID, иначе FIGI.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if is_valid_uuid(value) {
+            Ok(Self::Uid(InstrumentUid(value.to_owned())))
+        } else {
+            value.parse().map(Self::Figi)
+        }
+    }
+}
+
+impl fmt::Display for InstrumentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Строка не подходит под формат идентификатора.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidId {
+    kind: &'static str,
+    value: String,
+}
+
+impl InvalidId {
+    /// Вид идентификатора: `FIGI`, `ticker` и т.п.
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for InvalidId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {:?}", self.kind, self.value)
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_figi_checksum() {
+        assert!("BBG004730N88".parse::<Figi>().is_ok());
+        assert!("BBG000BLNNH6".parse::<Figi>().is_ok());
+        assert!("TCS00A0JPP37".parse::<Figi>().is_ok());
+        let error = "BBG000BLNNH7".parse::<Figi>().unwrap_err();
+        assert_eq!(error.kind(), "FIGI");
+        assert!("bbg004730n88".parse::<Figi>().is_err());
+        assert!("BBG004730N8".parse::<Figi>().is_err());
+    }
+
+    #[test]
+    fn validates_uuid() {
+        assert!("e6123145-9665-43e0-8413-cd61b8aa9b13"
+            .parse::<InstrumentUid>()
+            .is_ok());
+        assert!("e6123145966543e08413cd61b8aa9b13"
+            .parse::<InstrumentUid>()
+            .is_err());
+        assert!("e6123145-9665-43e0-8413-cd61b8aa9b1z"
+            .parse::<PositionUid>()
+            .is_err());
+        assert!(matches!(
+            "e6123145-9665-43e0-8413-cd61b8aa9b13".parse(),
+            Ok(InstrumentId::Uid(_))
+        ));
+        assert!(matches!("BBG004730N88".parse(), Ok(InstrumentId::Figi(_))));
+        assert!("".parse::<Ticker>().is_err());
+        assert!("SBER MX".parse::<Ticker>().is_err());
+    }
+}
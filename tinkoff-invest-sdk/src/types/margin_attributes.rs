@@ -1,6 +1,8 @@
 use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
 
 use super::MoneyValue;
+#[cfg(feature = "serde")]
+use crate::serialization::{proto_message_serde, MoneyRepr};
 
 #[repr(transparent)]
 #[derive(Debug, Clone)]
@@ -15,31 +17,42 @@ impl From<api::GetMarginAttributesResponse> for MarginAttributes {
 impl MarginAttributes {
     /// Ликвидная стоимость портфеля.
     #[inline(always)]
-    pub fn liquid_portfolio(&self) -> Option<MoneyValue> {
-        self.0.liquid_portfolio.clone().map(MoneyValue::from)
+    pub fn liquid_portfolio(&self) -> Option<MoneyValue<'_>> {
+        self.0.liquid_portfolio.as_ref().map(MoneyValue::from)
     }
 
     /// Начальная маржа — начальное обеспечение для совершения новой сделки.
     #[inline(always)]
-    pub fn starting_margin(&self) -> Option<MoneyValue> {
-        self.0.starting_margin.clone().map(MoneyValue::from)
+    pub fn starting_margin(&self) -> Option<MoneyValue<'_>> {
+        self.0.starting_margin.as_ref().map(MoneyValue::from)
     }
 
     /// Минимальная маржа — это минимальное обеспечение для поддержания позиции, которую вы уже открыли.
     #[inline(always)]
-    pub fn minimal_margin(&self) -> Option<MoneyValue> {
-        self.0.minimal_margin.clone().map(MoneyValue::from)
+    pub fn minimal_margin(&self) -> Option<MoneyValue<'_>> {
+        self.0.minimal_margin.as_ref().map(MoneyValue::from)
     }
 
     /// Уровень достаточности средств. Соотношение стоимости ликвидного портфеля к начальной марже.
     #[inline(always)]
     pub fn funds_sufficiency_level(&self) -> Option<Decimal> {
-        self.0.funds_sufficiency_level.clone().map(Decimal::from)
+        self.0.funds_sufficiency_level.as_ref().map(Decimal::from)
     }
 
     /// Объем недостающих средств. Разница между стартовой маржой и ликвидной стоимости портфеля.
     #[inline(always)]
-    pub fn amount_of_missing_funds(&self) -> Option<MoneyValue> {
-        self.0.amount_of_missing_funds.clone().map(MoneyValue::from)
+    pub fn amount_of_missing_funds(&self) -> Option<MoneyValue<'_>> {
+        self.0.amount_of_missing_funds.as_ref().map(MoneyValue::from)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    MarginAttributes.0: api::GetMarginAttributesResponse {
+        liquid_portfolio: Option<MoneyRepr>,
+        starting_margin: Option<MoneyRepr>,
+        minimal_margin: Option<MoneyRepr>,
+        funds_sufficiency_level: Option<Decimal>,
+        amount_of_missing_funds: Option<MoneyRepr>,
+    }
+}
@@ -1,6 +1,63 @@
-use chrono::{NaiveDate, NaiveDateTime};
+//! Типы SDK поверх сгенерированных типов API.
+//!
+//! Цены рыночных данных — свечей, стаканов, сделок и котировок — указаны, как в API, за одну
+//! штуку инструмента, а не за лот. Исключения: облигации котируются в процентах от номинала
+//! (см. [`BondPrice`]), фьючерсы — в пунктах (см. [`Future::points_to_money`]).
+
+use chrono::{DateTime, TimeZone, Utc};
 use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
 
+use crate::proto_enum;
+#[cfg(feature = "serde")]
+use crate::serialization::{proto_message_serde, MoneyRepr};
+
+mod bond_price;
+pub use bond_price::Aci;
+pub use bond_price::BondPrice;
+
+mod ids;
+pub use ids::AccountId;
+pub use ids::ClassCode;
+pub use ids::Figi;
+pub use ids::InstrumentId;
+pub use ids::InstrumentUid;
+pub use ids::InvalidId;
+pub use ids::PositionUid;
+pub use ids::Ticker;
+
+mod candle;
+pub use candle::Candle;
+pub use candle::CandleInterval;
+
+mod future;
+pub use future::Future;
+pub use future::FuturesMargin;
+
+mod order_book;
+pub use order_book::OrderBook;
+pub use order_book::OrderBookLevel;
+
+mod quotes;
+pub use quotes::ClosePrice;
+pub use quotes::LastPrice;
+pub use quotes::TradingStatus;
+
+mod trade;
+pub use trade::Trade;
+pub use trade::TradeDirection;
+
+mod money;
+pub use money::Currency;
+pub use money::Money;
+pub use money::MoneyError;
+pub use money::ParseMoneyError;
+
+mod time;
+pub(crate) use time::moscow_day_start;
+pub use time::moscow_offset;
+pub use time::MoscowTime;
+pub use time::TimestampBound;
+
 mod trading_schedule;
 pub use trading_schedule::TradingDay;
 pub use trading_schedule::TradingSchedule;
@@ -27,12 +84,12 @@ impl From<api::Coupon> for Coupon {
 
 impl Coupon {
     /// Figi-идентификатор инструмента.
-    pub fn figi(&self) -> &str {
-        &self.inner.figi
+    pub fn figi(&self) -> &Figi {
+        Figi::from_ref(&self.inner.figi)
     }
     
     /// Дата выплаты купона.
-    pub fn coupon_date(&self) -> Option<NaiveDateTime>{
+    pub fn coupon_date(&self) -> Option<DateTime<Utc>>{
         self.inner.coupon_date.as_ref().and_then(|ts| grpc_timestamp_to_chrono_timestamp(ts))
     }
 
@@ -42,41 +99,30 @@ impl Coupon {
     }
 
     /// (Опционально) Дата фиксации реестра для выплаты купона.
-    pub fn fix_date(&self) ->Option<NaiveDateTime> {
+    pub fn fix_date(&self) ->Option<DateTime<Utc>> {
         self.inner.fix_date.as_ref().and_then(|ts| grpc_timestamp_to_chrono_timestamp(ts))
     }
 
     /// Выплата на одну облигацию.
-    pub fn pay_one_bond(&self) -> Option<MoneyValue>{
-        self.inner.pay_one_bond.map(Into::into)
+    pub fn pay_one_bond(&self) -> Option<MoneyValue<'_>> {
+        self.inner.pay_one_bond.as_ref().map(Into::into)
     }
     
     /// Тип купона.
-    pub fn coupon_type(&self) -> Option<CouponType> {
-        match self.inner.coupon_type {
-            1 => Some(CouponType::Constant),
-            2 => Some(CouponType::Floating),
-            3 => Some(CouponType::Discount),
-            4 => Some(CouponType::Mortgage),
-            5 => Some(CouponType::Fix),
-            6 => Some(CouponType::Variable),
-            7 => Some(CouponType::Other),
-            _ => None
-        }
+    pub fn coupon_type(&self) -> CouponType {
+        self.inner.coupon_type.into()
     }
     
     /// Начало купонного периода.
-    pub fn coupon_start_date(&self) -> Option<NaiveDate> {
+    pub fn coupon_start_date(&self) -> Option<DateTime<Utc>> {
         self.inner.coupon_start_date.as_ref()
         .and_then(grpc_timestamp_to_chrono_timestamp)
-        .map(|x|x.date())
     }
     
     /// Окончание купонного периода.
-    pub fn coupon_end_date(&self) -> Option<NaiveDate> {
+    pub fn coupon_end_date(&self) -> Option<DateTime<Utc>> {
         self.inner.coupon_end_date.as_ref()
         .and_then(grpc_timestamp_to_chrono_timestamp)
-        .map(|x|x.date())
     }
     
     /// Купонный период в днях.
@@ -85,167 +131,170 @@ impl Coupon {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-#[repr(i32)]
-pub enum CouponType {
-    /// Постоянный
-    Constant,
-    /// Плавающий
-    Floating,
-    /// Дисконт
-    Discount,
-    /// Ипотечный
-    Mortgage,
-    /// Фиксированный
-    Fix,
-    /// Переменный
-    Variable,
-    /// Прочее
-    Other,
+proto_enum! {
+    pub enum CouponType: CouponType {
+        /// Неопределённое значение
+        Unspecified,
+        /// Постоянный
+        Constant,
+        /// Плавающий
+        Floating,
+        /// Дисконт
+        Discount,
+        /// Ипотечный
+        Mortgage,
+        /// Фиксированный
+        Fix,
+        /// Переменный
+        Variable,
+        /// Прочее
+        Other,
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum InstrumentsList {
     Base,
     All,
 }
 
-#[derive(Debug, Clone,)]
+/// Денежная сумма, ссылающаяся на данные ответа API.
+#[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
-pub struct MoneyValue(api::MoneyValue);
+pub struct MoneyValue<'a>(&'a api::MoneyValue);
 
-impl From<api::MoneyValue> for MoneyValue {
-    fn from(value: api::MoneyValue) -> Self {
+impl<'a> From<&'a api::MoneyValue> for MoneyValue<'a> {
+    #[inline(always)]
+    fn from(value: &'a api::MoneyValue) -> Self {
         Self(value)
     }
 }
 
-impl MoneyValue {
-    pub fn currency(&self) -> &str {
+impl<'a> MoneyValue<'a> {
+    #[inline]
+    pub fn currency(&self) -> &'a str {
         &self.0.currency
     }
 
+    #[inline]
     pub fn value(&self) -> Decimal {
         self.0.into()
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CountryOfRisk {
-    // code: String,
-    // name: String,
+#[cfg(feature = "serde")]
+impl serde::Serialize for MoneyValue<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use crate::serialization::FromRaw;
+        Option::<MoneyRepr>::from_raw(&Some(self.0.clone())).serialize(serializer)
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum SecurityTradingStatus {
-    /// Торговый статус не определён
-    Unspecified,
-    /// Недоступен для торгов
-    NotAvailableForTrading,
-    /// Период открытия торгов
-    OpeningPeriod,
-    /// Период закрытия торгов
-    ClosingPeriod,
-    /// Перерыв в торговле
-    BreakInTrading,
-    /// Нормальная торговля
-    NormalTrading,
-    /// Аукцион закрытия
-    ClosingAuction,
-    /// Аукцион крупных пакетов
-    DarkPoolAuction,
-    /// Дискретный аукцион
-    DiscreteAuction,
-    /// Аукцион открытия
-    OpeningAuctionPeriod,
-    /// Период торгов по цене аукциона закрытия
-    TradingAtClosingAuctionPrice,
-    /// Сессия назначена
-    SessionAssigned,
-    /// Сессия закрыта
-    SessionClose,
-    /// Сессия открыта
-    SessionOpen,
-    /// Доступна торговля в режиме внутренней ликвидности брокера
-    DealerNormalTrading,
-    /// Перерыв торговли в режиме внутренней ликвидности брокера
-    DealerBreakInTrading,
-    /// Недоступна торговля в режиме внутренней ликвидности брокера
-    DealerNotAvailableForTrading,
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CountryOfRisk<'a> {
+    code: &'a str,
+    name: &'a str,
 }
 
-impl From<api::SecurityTradingStatus> for SecurityTradingStatus {
-    fn from(value: api::SecurityTradingStatus) -> Self {
-        match value {
-            api::SecurityTradingStatus::Unspecified => Self::Unspecified,
-            api::SecurityTradingStatus::NotAvailableForTrading => Self::NotAvailableForTrading,
-            api::SecurityTradingStatus::OpeningPeriod => Self::OpeningPeriod,
-            api::SecurityTradingStatus::ClosingPeriod => Self::ClosingPeriod,
-            api::SecurityTradingStatus::BreakInTrading => Self::BreakInTrading,
-            api::SecurityTradingStatus::NormalTrading => Self::NormalTrading,
-            api::SecurityTradingStatus::ClosingAuction => Self::ClosingAuction,
-            api::SecurityTradingStatus::DarkPoolAuction => Self::DarkPoolAuction,
-            api::SecurityTradingStatus::DiscreteAuction => Self::DiscreteAuction,
-            api::SecurityTradingStatus::OpeningAuctionPeriod => Self::OpeningAuctionPeriod,
-            api::SecurityTradingStatus::TradingAtClosingAuctionPrice => {
-                Self::TradingAtClosingAuctionPrice
-            }
-            api::SecurityTradingStatus::SessionAssigned => Self::SessionAssigned,
-            api::SecurityTradingStatus::SessionClose => Self::SessionClose,
-            api::SecurityTradingStatus::SessionOpen => Self::SessionOpen,
-            api::SecurityTradingStatus::DealerNormalTrading => Self::DealerNormalTrading,
-            api::SecurityTradingStatus::DealerBreakInTrading => Self::DealerBreakInTrading,
-            api::SecurityTradingStatus::DealerNotAvailableForTrading => {
-                Self::DealerNotAvailableForTrading
-            }
-        }
+impl<'a> CountryOfRisk<'a> {
+    /// Код страны.
+    #[inline]
+    pub fn code(&self) -> &'a str {
+        self.code
+    }
+
+    /// Наименование страны.
+    #[inline]
+    pub fn name(&self) -> &'a str {
+        self.name
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+proto_enum! {
+    pub enum SecurityTradingStatus: SecurityTradingStatus {
+        /// Торговый статус не определён
+        Unspecified,
+        /// Недоступен для торгов
+        NotAvailableForTrading,
+        /// Период открытия торгов
+        OpeningPeriod,
+        /// Период закрытия торгов
+        ClosingPeriod,
+        /// Перерыв в торговле
+        BreakInTrading,
+        /// Нормальная торговля
+        NormalTrading,
+        /// Аукцион закрытия
+        ClosingAuction,
+        /// Аукцион крупных пакетов
+        DarkPoolAuction,
+        /// Дискретный аукцион
+        DiscreteAuction,
+        /// Аукцион открытия
+        OpeningAuctionPeriod,
+        /// Период торгов по цене аукциона закрытия
+        TradingAtClosingAuctionPrice,
+        /// Сессия назначена
+        SessionAssigned,
+        /// Сессия закрыта
+        SessionClose,
+        /// Сессия открыта
+        SessionOpen,
+        /// Доступна торговля в режиме внутренней ликвидности брокера
+        DealerNormalTrading,
+        /// Перерыв торговли в режиме внутренней ликвидности брокера
+        DealerBreakInTrading,
+        /// Недоступна торговля в режиме внутренней ликвидности брокера
+        DealerNotAvailableForTrading,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum BondIssueKind {
-    Unknown,
+    /// Значение, неизвестное этой версии SDK, в том виде, в котором его вернул API.
+    Unknown(String),
     Documentary,
     NonDocumentary,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum RealExchange {
-    Unspecified,
-    Moex,
-    Rts,
-    Otc,
+proto_enum! {
+    pub enum RealExchange: RealExchange {
+        Unspecified,
+        Moex,
+        Rts,
+        Otc,
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Ставки риска короткой позиции.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Short {
-    // /// Коэффициент ставки риска короткой позиции по инструменту.
-    // kshort: Decimal,
-    // /// Ставка риска минимальной маржи в шорт.
-    // dshort: Decimal,
-    // /// Ставка риска начальной маржи в шорт.
-    // dshort_min: Decimal,
+    /// Коэффициент ставки риска короткой позиции по инструменту.
+    pub kshort: Decimal,
+    /// Ставка риска минимальной маржи в шорт.
+    pub dshort: Decimal,
+    /// Ставка риска начальной маржи в шорт.
+    pub dshort_min: Decimal,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Ставки риска длинной позиции.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Long {
-    // /// Коэффициент ставки риска длинной позиции по инструменту.
-    // klong: Decimal,
-    // /// Ставка риска минимальной маржи в лонг.
-    // dlong: Decimal,
-    // /// Ставка риска начальной маржи в лонг.
-    // dlong_min: Decimal,
-}
-impl From<api::RealExchange> for RealExchange {
-    fn from(value: api::RealExchange) -> Self {
-        match value {
-            api::RealExchange::Unspecified => Self::Unspecified,
-            api::RealExchange::Moex => Self::Moex,
-            api::RealExchange::Rts => Self::Rts,
-            api::RealExchange::Otc => Self::Otc,
-        }
-    }
+    /// Коэффициент ставки риска длинной позиции по инструменту.
+    pub klong: Decimal,
+    /// Ставка риска минимальной маржи в лонг.
+    pub dlong: Decimal,
+    /// Ставка риска начальной маржи в лонг.
+    pub dlong_min: Decimal,
 }
+
 #[derive(Debug, Clone)]
 pub struct Bond(api::Bond);
 
@@ -257,18 +306,18 @@ impl From<api::Bond> for Bond {
 
 impl Bond {
     #[inline]
-    pub fn figi(&self) -> &str {
-        &self.0.figi
+    pub fn figi(&self) -> &Figi {
+        Figi::from_ref(&self.0.figi)
     }
 
     #[inline]
-    pub fn ticker(&self) -> &str {
-        &self.0.ticker
+    pub fn ticker(&self) -> &Ticker {
+        Ticker::from_ref(&self.0.ticker)
     }
 
     #[inline]
-    pub fn class_code(&self) -> &str {
-        &self.0.class_code
+    pub fn class_code(&self) -> &ClassCode {
+        ClassCode::from_ref(&self.0.class_code)
     }
 
     #[inline]
@@ -289,11 +338,10 @@ impl Bond {
     #[inline]
     pub fn short(&self) -> Option<Short> {
         if self.0.short_enabled_flag {
-            // TODO extra allocation
             Some(Short {
-                // kshort: self.0.kshort.clone().map(Into::into)?,
-                // dshort: self.0.dshort.clone().map(Into::into)?,
-                // dshort_min: self.0.dshort_min.clone().map(Into::into)?,
+                kshort: self.0.kshort.as_ref().map(Into::into)?,
+                dshort: self.0.dshort.as_ref().map(Into::into)?,
+                dshort_min: self.0.dshort_min.as_ref().map(Into::into)?,
             })
         } else {
             None
@@ -302,11 +350,10 @@ impl Bond {
 
     #[inline]
     pub fn long(&self) -> Option<Long> {
-        // TODO extra allocation
         Some(Long {
-            // klong: self.0.klong.clone().map(Into::into)?,
-            // dlong: self.0.dlong.clone().map(Into::into)?,
-            // dlong_min: self.0.dlong_min.clone().map(Into::into)?,
+            klong: self.0.klong.as_ref().map(Into::into)?,
+            dlong: self.0.dlong.as_ref().map(Into::into)?,
+            dlong_min: self.0.dlong_min.as_ref().map(Into::into)?,
         })
     }
 
@@ -321,54 +368,49 @@ impl Bond {
     }
 
     #[inline]
-    pub fn maturity_date(&self) -> Option<NaiveDate> {
+    pub fn maturity_date(&self) -> Option<DateTime<Utc>> {
         self.0
             .maturity_date
             .as_ref()
             .and_then(grpc_timestamp_to_chrono_timestamp)
-            .map(|d| d.date())
     }
 
     #[inline]
-    pub fn nominal(&self) -> Option<MoneyValue> {
-        // TODO extra allocation
-        self.0.nominal.clone().map(Into::into)
+    pub fn nominal(&self) -> Option<MoneyValue<'_>> {
+        self.0.nominal.as_ref().map(Into::into)
     }
 
     #[inline]
-    pub fn state_reg_date(&self) -> Option<NaiveDate> {
+    pub fn state_reg_date(&self) -> Option<DateTime<Utc>> {
         self.0
             .state_reg_date
             .as_ref()
             .and_then(grpc_timestamp_to_chrono_timestamp)
-            .map(|d| d.date())
     }
 
     #[inline]
-    pub fn placement_date(&self) -> Option<NaiveDate> {
+    pub fn placement_date(&self) -> Option<DateTime<Utc>> {
         self.0
             .placement_date
             .as_ref()
             .and_then(grpc_timestamp_to_chrono_timestamp)
-            .map(|d| d.date())
     }
 
     #[inline]
-    pub fn placement_price(&self) -> Option<MoneyValue> {
-        self.0.placement_price.clone().map(Into::into)
+    pub fn placement_price(&self) -> Option<MoneyValue<'_>> {
+        self.0.placement_price.as_ref().map(Into::into)
     }
 
     #[inline]
-    pub fn aci_value(&self) -> Option<MoneyValue> {
-        self.0.aci_value.clone().map(Into::into)
+    pub fn aci_value(&self) -> Option<MoneyValue<'_>> {
+        self.0.aci_value.as_ref().map(Into::into)
     }
 
     #[inline]
-    pub fn country_of_risk(&self) -> CountryOfRisk {
-        // TODO extra allocation
+    pub fn country_of_risk(&self) -> CountryOfRisk<'_> {
         CountryOfRisk {
-            // code: self.0.country_of_risk.clone(),
-            // name: self.0.country_of_risk_name.clone(),
+            code: &self.0.country_of_risk,
+            name: &self.0.country_of_risk_name,
         }
     }
 
@@ -382,7 +424,7 @@ impl Bond {
         match self.0.issue_kind.as_str() {
             "documentary" => BondIssueKind::Documentary,
             "non_documentary" => BondIssueKind::NonDocumentary,
-            _ => BondIssueKind::Unknown,
+            other => BondIssueKind::Unknown(other.to_owned()),
         }
     }
 
@@ -398,7 +440,7 @@ impl Bond {
 
     #[inline]
     pub fn trading_status(&self) -> SecurityTradingStatus {
-        self.0.trading_status().into()
+        self.0.trading_status.into()
     }
     #[inline]
     pub fn is_otc(&self) -> bool {
@@ -432,8 +474,7 @@ impl Bond {
 
     #[inline]
     pub fn min_price_increment(&self) -> Option<Decimal> {
-        // TODO allocation
-        self.0.min_price_increment.clone().map(Into::into)
+        self.0.min_price_increment.as_ref().map(Into::into)
     }
 
     #[inline]
@@ -442,18 +483,18 @@ impl Bond {
     }
 
     #[inline]
-    pub fn uid(&self) -> &str {
-        &self.0.uid
+    pub fn uid(&self) -> &InstrumentUid {
+        InstrumentUid::from_ref(&self.0.uid)
     }
 
     #[inline]
     pub fn real_exchange(&self) -> RealExchange {
-        self.0.real_exchange().into()
+        self.0.real_exchange.into()
     }
 
     #[inline]
-    pub fn position_uid(&self) -> &str {
-        &self.0.position_uid
+    pub fn position_uid(&self) -> &PositionUid {
+        PositionUid::from_ref(&self.0.position_uid)
     }
 
     #[inline]
@@ -462,17 +503,15 @@ impl Bond {
     }
 
     #[inline]
-    pub fn first_minute_candle_date(&self) -> Option<NaiveDateTime> {
-        // TODO allocation
+    pub fn first_minute_candle_date(&self) -> Option<DateTime<Utc>> {
         self.0
-            .first_1day_candle_date
+            .first_1min_candle_date
             .as_ref()
             .and_then(grpc_timestamp_to_chrono_timestamp)
     }
 
     #[inline]
-    pub fn first_day_candle_date(&self) -> Option<NaiveDateTime> {
-        // TODO allocation
+    pub fn first_day_candle_date(&self) -> Option<DateTime<Utc>> {
         self.0
             .first_1day_candle_date
             .as_ref()
@@ -480,40 +519,37 @@ impl Bond {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum AccountType {
-    Unspecified,
-    Tinkoff,
-    TinkoffIis,
-    InvestBox,
+proto_enum! {
+    pub enum AccountType: AccountType {
+        Unspecified,
+        Tinkoff,
+        TinkoffIis,
+        InvestBox,
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum AccountAccessLevel {
-    /// Уровень доступа не определён.
-    Unspecified,
-    /// Полный доступ к счёту.
-    FullAccess,
-    /// Доступ с уровнем прав "только чтение".
-    ReadOnly,
-    /// Доступ отсутствует.
-    NoAccess,
+proto_enum! {
+    pub enum AccountAccessLevel: AccessLevel {
+        /// Уровень доступа не определён.
+        Unspecified = AccountAccessLevelUnspecified,
+        /// Полный доступ к счёту.
+        FullAccess = AccountAccessLevelFullAccess,
+        /// Доступ с уровнем прав "только чтение".
+        ReadOnly = AccountAccessLevelReadOnly,
+        /// Доступ отсутствует.
+        NoAccess = AccountAccessLevelNoAccess,
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Account(api::Account);
 impl Account {
-    pub fn id(&self) -> &str {
-        &self.0.id
+    pub fn id(&self) -> &AccountId {
+        AccountId::from_ref(&self.0.id)
     }
 
     pub fn account_type(&self) -> AccountType {
-        match self.0.r#type {
-            1 => AccountType::Tinkoff,
-            2 => AccountType::TinkoffIis,
-            3 => AccountType::InvestBox,
-            _ => AccountType::Unspecified,
-        }
+        self.0.r#type.into()
     }
 
     pub fn name(&self) -> &str {
@@ -521,46 +557,35 @@ impl Account {
     }
 
     pub fn status(&self) -> AccountStatus {
-        match self.0.status() {
-            api::AccountStatus::Unspecified => AccountStatus::Unspecified,
-            api::AccountStatus::New => AccountStatus::New,
-            api::AccountStatus::Open => AccountStatus::Open,
-            api::AccountStatus::Closed => AccountStatus::Closed,
-        }
+        self.0.status.into()
     }
 
-    pub fn opened_date(&self) -> Option<NaiveDate> {
+    pub fn opened_date(&self) -> Option<DateTime<Utc>> {
         self.0
             .opened_date
             .as_ref()
             .and_then(grpc_timestamp_to_chrono_timestamp)
-            .map(|t| t.date())
     }
 
-    pub fn closed_date(&self) -> Option<NaiveDate> {
+    pub fn closed_date(&self) -> Option<DateTime<Utc>> {
         self.0
             .closed_date
             .as_ref()
             .and_then(grpc_timestamp_to_chrono_timestamp)
-            .map(|t| t.date())
     }
 
     pub fn access_level(&self) -> AccountAccessLevel {
-        match self.0.access_level() {
-            api::AccessLevel::AccountAccessLevelUnspecified => AccountAccessLevel::Unspecified,
-            api::AccessLevel::AccountAccessLevelFullAccess => AccountAccessLevel::FullAccess,
-            api::AccessLevel::AccountAccessLevelReadOnly => AccountAccessLevel::ReadOnly,
-            api::AccessLevel::AccountAccessLevelNoAccess => AccountAccessLevel::NoAccess,
-        }
+        self.0.access_level.into()
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub enum AccountStatus {
-    Unspecified,
-    New,
-    Open,
-    Closed,
+proto_enum! {
+    pub enum AccountStatus: AccountStatus {
+        Unspecified,
+        New,
+        Open,
+        Closed,
+    }
 }
 
 impl From<api::Account> for Account {
@@ -569,12 +594,130 @@ impl From<api::Account> for Account {
     }
 }
 
-pub(crate) fn grpc_timestamp_to_chrono_timestamp(t: &prost_types::Timestamp) -> Option<NaiveDateTime> {
-    NaiveDateTime::from_timestamp_opt(t.seconds, t.nanos as u32)
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    Coupon.inner: api::Coupon {
+        figi: String,
+        coupon_date: Option<DateTime<Utc>>,
+        coupon_number: i64,
+        fix_date: Option<DateTime<Utc>>,
+        pay_one_bond: Option<MoneyRepr>,
+        coupon_type: CouponType,
+        coupon_start_date: Option<DateTime<Utc>>,
+        coupon_end_date: Option<DateTime<Utc>>,
+        coupon_period: i32,
+    }
 }
 
-pub(crate) fn chrono_timestamp_to_grpc_timestamp(t: NaiveDateTime) -> prost_types::Timestamp {
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    Bond.0: api::Bond {
+        figi: String,
+        ticker: String,
+        class_code: String,
+        isin: String,
+        lot: i32,
+        currency: String,
+        klong: Option<Decimal>,
+        kshort: Option<Decimal>,
+        dlong: Option<Decimal>,
+        dshort: Option<Decimal>,
+        dlong_min: Option<Decimal>,
+        dshort_min: Option<Decimal>,
+        short_enabled_flag: bool,
+        name: String,
+        coupon_quantity_per_year: i32,
+        maturity_date: Option<DateTime<Utc>>,
+        nominal: Option<MoneyRepr>,
+        state_reg_date: Option<DateTime<Utc>>,
+        placement_date: Option<DateTime<Utc>>,
+        placement_price: Option<MoneyRepr>,
+        aci_value: Option<MoneyRepr>,
+        country_of_risk: String,
+        country_of_risk_name: String,
+        sector: String,
+        issue_kind: String,
+        issue_size: i64,
+        issue_size_plan: i64,
+        trading_status: SecurityTradingStatus,
+        otc_flag: bool,
+        buy_available_flag: bool,
+        sell_available_flag: bool,
+        floating_coupon_flag: bool,
+        perpetual_flag: bool,
+        amortization_flag: bool,
+        min_price_increment: Option<Decimal>,
+        api_trade_available_flag: bool,
+        uid: String,
+        real_exchange: RealExchange,
+        position_uid: String,
+        for_iis_flag: bool,
+        first_1min_candle_date: Option<DateTime<Utc>>,
+        first_1day_candle_date: Option<DateTime<Utc>>,
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    Account.0: api::Account {
+        id: String,
+        r#type: AccountType,
+        name: String,
+        status: AccountStatus,
+        opened_date: Option<DateTime<Utc>>,
+        closed_date: Option<DateTime<Utc>>,
+        access_level: AccountAccessLevel,
+    }
+}
+
+pub(crate) fn grpc_timestamp_to_chrono_timestamp(t: &tinkoff_invest_grpc::Timestamp) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(t.seconds, t.nanos as u32).single()
+}
+
+pub(crate) fn chrono_timestamp_to_grpc_timestamp(t: DateTime<Utc>) -> tinkoff_invest_grpc::Timestamp {
     let seconds = t.timestamp();
     let nanos = t.timestamp_subsec_nanos() as i32;
-    prost_types::Timestamp { seconds, nanos }
+    tinkoff_invest_grpc::Timestamp { seconds, nanos }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unknown_proto_values() {
+        assert_eq!(CouponType::from(2), CouponType::Floating);
+        assert_eq!(CouponType::from(42), CouponType::Unknown(42));
+        assert_eq!(i32::from(CouponType::Unknown(42)), 42);
+        assert_eq!(
+            AccountAccessLevel::from(api::AccessLevel::AccountAccessLevelReadOnly),
+            AccountAccessLevel::ReadOnly
+        );
+        assert_eq!(i32::from(AccountAccessLevel::ReadOnly), 2);
+
+        let bond = |issue_kind: &str| {
+            Bond::from(api::Bond {
+                issue_kind: issue_kind.to_owned(),
+                ..Default::default()
+            })
+        };
+        assert_eq!(bond("documentary").issue_kind(), BondIssueKind::Documentary);
+        assert_eq!(
+            bond("electronic").issue_kind(),
+            BondIssueKind::Unknown("electronic".to_owned())
+        );
+    }
+
+    #[test]
+    fn first_candle_dates() {
+        let minute = Utc.with_ymd_and_hms(2018, 3, 7, 7, 0, 0).unwrap();
+        let day = Utc.with_ymd_and_hms(2010, 8, 17, 0, 0, 0).unwrap();
+        let bond = Bond::from(api::Bond {
+            first_1min_candle_date: Some(chrono_timestamp_to_grpc_timestamp(minute)),
+            first_1day_candle_date: Some(chrono_timestamp_to_grpc_timestamp(day)),
+            ..Default::default()
+        });
+        assert_eq!(bond.first_minute_candle_date(), Some(minute));
+        assert_eq!(bond.first_day_candle_date(), Some(day));
+    }
 }
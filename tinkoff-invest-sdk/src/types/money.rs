@@ -0,0 +1,352 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use tinkoff_invest_grpc::api;
+use tinkoff_invest_grpc::decimal::rust_decimal::{Decimal, RoundingStrategy};
+use tinkoff_invest_grpc::DecimalConversionError;
+
+use super::MoneyValue;
+
+macro_rules! currencies {
+    ($($variant:ident => $code:literal, $minor:expr;)*) => {
+        /// Валюта: коды ISO 4217, в том числе драгоценные металлы, которыми торгует брокер.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[non_exhaustive]
+        pub enum Currency {
+            $($variant,)*
+            /// Прочая валюта: три заглавные латинские буквы.
+            Other([u8; 3]),
+        }
+
+        impl Currency {
+            /// Код валюты заглавными буквами.
+            pub fn code(&self) -> &str {
+                match self {
+                    $(Self::$variant => $code,)*
+                    Self::Other(code) => std::str::from_utf8(code).unwrap_or("???"),
+                }
+            }
+
+            /// Число знаков после запятой в минимальной денежной единице.
+            /// `None`, если её нет (драгоценные металлы).
+            pub fn minor_units(&self) -> Option<u32> {
+                match self {
+                    $(Self::$variant => $minor,)*
+                    Self::Other(_) => Some(2),
+                }
+            }
+        }
+
+        impl FromStr for Currency {
+            type Err = ParseMoneyError;
+
+            /// Код без учёта регистра: API отдаёт коды строчными буквами.
+            fn from_str(code: &str) -> Result<Self, Self::Err> {
+                let code: [u8; 3] = code
+                    .as_bytes()
+                    .try_into()
+                    .map_err(|_| ParseMoneyError::InvalidCurrency)?;
+                if !code.iter().all(u8::is_ascii_alphabetic) {
+                    return Err(ParseMoneyError::InvalidCurrency);
+                }
+                let code = code.map(|c| c.to_ascii_uppercase());
+                Ok(match &code {
+                    $(c if c == $code.as_bytes() => Self::$variant,)*
+                    _ => Self::Other(code),
+                })
+            }
+        }
+    };
+}
+
+currencies! {
+    Rub => "RUB", Some(2);
+    Usd => "USD", Some(2);
+    Eur => "EUR", Some(2);
+    Cny => "CNY", Some(2);
+    Hkd => "HKD", Some(2);
+    Gbp => "GBP", Some(2);
+    Chf => "CHF", Some(2);
+    Jpy => "JPY", Some(0);
+    Try => "TRY", Some(2);
+    Kzt => "KZT", Some(2);
+    Byn => "BYN", Some(2);
+    Amd => "AMD", Some(2);
+    Uzs => "UZS", Some(2);
+    Kgs => "KGS", Some(2);
+    Tjs => "TJS", Some(2);
+    Xau => "XAU", None;
+    Xag => "XAG", None;
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Сериализуется кодом валюты.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Currency {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Currency {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Сумма в валюте. Арифметика проверяет, что валюты совпадают.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Money {
+    amount: Decimal,
+    currency: Currency,
+}
+
+impl Money {
+    #[inline]
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    #[inline]
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    #[inline]
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    #[inline]
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// Округлить до минимальной денежной единицы, половину — от нуля.
+    pub fn round(&self) -> Self {
+        self.round_with(RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    pub fn round_with(&self, strategy: RoundingStrategy) -> Self {
+        match self.currency.minor_units() {
+            Some(dp) => Self::new(
+                self.amount.round_dp_with_strategy(dp, strategy),
+                self.currency,
+            ),
+            None => *self,
+        }
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+        let currency = self.same_currency(&other)?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::new(amount, currency))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+        self.checked_add(-other)
+    }
+
+    pub fn checked_mul(self, factor: Decimal) -> Result<Money, MoneyError> {
+        let amount = self
+            .amount
+            .checked_mul(factor)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::new(amount, self.currency))
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<Currency, MoneyError> {
+        if self.currency == other.currency {
+            Ok(self.currency)
+        } else {
+            Err(MoneyError::CurrencyMismatch {
+                left: self.currency,
+                right: other.currency,
+            })
+        }
+    }
+}
+
+impl Add for Money {
+    type Output = Result<Money, MoneyError>;
+
+    fn add(self, other: Money) -> Self::Output {
+        self.checked_add(other)
+    }
+}
+
+impl Sub for Money {
+    type Output = Result<Money, MoneyError>;
+
+    fn sub(self, other: Money) -> Self::Output {
+        self.checked_sub(other)
+    }
+}
+
+impl Mul<Decimal> for Money {
+    type Output = Result<Money, MoneyError>;
+
+    fn mul(self, factor: Decimal) -> Self::Output {
+        self.checked_mul(factor)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Self::new(-self.amount, self.currency)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
+
+impl FromStr for Money {
+    type Err = ParseMoneyError;
+
+    /// Сумма и код валюты через пробел: `1234.56 RUB`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (amount, currency) = s
+            .trim()
+            .rsplit_once(' ')
+            .ok_or(ParseMoneyError::InvalidFormat)?;
+        let amount =
+            Decimal::from_str(amount.trim()).map_err(|_| ParseMoneyError::InvalidAmount)?;
+        Ok(Self::new(amount, currency.parse()?))
+    }
+}
+
+impl TryFrom<MoneyValue<'_>> for Money {
+    type Error = ParseMoneyError;
+
+    fn try_from(value: MoneyValue<'_>) -> Result<Self, Self::Error> {
+        Ok(Self::new(value.value(), value.currency().parse()?))
+    }
+}
+
+impl TryFrom<Money> for api::MoneyValue {
+    type Error = DecimalConversionError;
+
+    /// Код валюты передаётся строчными буквами, как его принимает API.
+    fn try_from(money: Money) -> Result<Self, Self::Error> {
+        (money.currency.code().to_ascii_lowercase(), money.amount).try_into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MoneyError {
+    /// Операция над суммами в разных валютах.
+    CurrencyMismatch {
+        left: Currency,
+        right: Currency,
+    },
+    Overflow,
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CurrencyMismatch { left, right } => {
+                write!(f, "currency mismatch: {left} and {right}")
+            }
+            Self::Overflow => write!(f, "amount overflow"),
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseMoneyError {
+    /// Ожидается сумма и код валюты через пробел.
+    InvalidFormat,
+    InvalidAmount,
+    /// Код валюты должен состоять из трёх латинских букв.
+    InvalidCurrency,
+}
+
+impl fmt::Display for ParseMoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "expected amount and currency code"),
+            Self::InvalidAmount => write!(f, "invalid amount"),
+            Self::InvalidCurrency => write!(f, "invalid currency code"),
+        }
+    }
+}
+
+impl std::error::Error for ParseMoneyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rub(amount: &str) -> Money {
+        Money::new(amount.parse().unwrap(), Currency::Rub)
+    }
+
+    #[test]
+    fn refuses_to_mix_currencies() {
+        let usd = Money::new(Decimal::ONE, Currency::Usd);
+        assert_eq!(rub("10.5") + rub("0.25"), Ok(rub("10.75")));
+        assert_eq!(rub("10.5") - rub("0.5"), Ok(rub("10")));
+        assert_eq!(rub("10.5") * Decimal::from(2), Ok(rub("21")));
+        assert_eq!(
+            rub("1") + usd,
+            Err(MoneyError::CurrencyMismatch {
+                left: Currency::Rub,
+                right: Currency::Usd
+            })
+        );
+    }
+
+    #[test]
+    fn rounds_to_minor_units() {
+        assert_eq!(rub("10.125").round(), rub("10.13"));
+        assert_eq!(rub("-10.125").round(), rub("-10.13"));
+        let yen = Money::new("150.5".parse().unwrap(), Currency::Jpy);
+        assert_eq!(yen.round().amount(), Decimal::from(151));
+    }
+
+    #[test]
+    fn parses_and_displays() {
+        let money: Money = "1234.50 rub".parse().unwrap();
+        assert_eq!(money, rub("1234.5"));
+        assert_eq!(money.to_string(), "1234.50 RUB");
+        assert_eq!("aed".parse(), Ok(Currency::Other(*b"AED")));
+        assert_eq!(Currency::Other(*b"AED").code(), "AED");
+        assert_eq!(
+            "rub1".parse::<Currency>(),
+            Err(ParseMoneyError::InvalidCurrency)
+        );
+        assert_eq!(
+            "12,5 RUB".parse::<Money>(),
+            Err(ParseMoneyError::InvalidAmount)
+        );
+    }
+
+    #[test]
+    fn converts_to_api_money_value() {
+        let value: api::MoneyValue = rub("-1.5").try_into().unwrap();
+        assert_eq!(value.currency, "rub");
+        assert_eq!((value.units, value.nano), (-1, -500_000_000));
+    }
+}
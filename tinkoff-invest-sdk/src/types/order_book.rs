@@ -0,0 +1,264 @@
+use chrono::{DateTime, Utc};
+use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
+
+use super::{grpc_timestamp_to_chrono_timestamp, Figi, InstrumentUid};
+use crate::margin::OrderDirection;
+
+/// Уровень стакана.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderBookLevel {
+    pub price: Decimal,
+    /// Количество в лотах.
+    pub quantity: i64,
+}
+
+/// Снимок стакана. Заявки на покупку — по убыванию цены, на продажу — по возрастанию.
+/// Уровни, для которых API не вернул цену, отбрасываются.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderBook {
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "Figi::deserialize_unchecked")
+    )]
+    pub figi: Figi,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "InstrumentUid::deserialize_unchecked")
+    )]
+    pub instrument_uid: InstrumentUid,
+    pub depth: i32,
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+    pub last_price: Option<Decimal>,
+    pub close_price: Option<Decimal>,
+    /// Верхний лимит цены.
+    pub limit_up: Option<Decimal>,
+    /// Нижний лимит цены.
+    pub limit_down: Option<Decimal>,
+    pub last_price_time: Option<DateTime<Utc>>,
+    pub close_price_time: Option<DateTime<Utc>>,
+    /// Время формирования стакана на бирже.
+    pub time: Option<DateTime<Utc>>,
+}
+
+impl From<api::GetOrderBookResponse> for OrderBook {
+    fn from(response: api::GetOrderBookResponse) -> Self {
+        let levels = |orders: &[api::Order]| {
+            orders
+                .iter()
+                .filter_map(|order| {
+                    Some(OrderBookLevel {
+                        price: order.price.as_ref().map(Decimal::from)?,
+                        quantity: order.quantity,
+                    })
+                })
+                .collect()
+        };
+        let price = |price: &Option<api::Quotation>| price.as_ref().map(Decimal::from);
+        let time = |time: &Option<tinkoff_invest_grpc::Timestamp>| {
+            time.as_ref().and_then(grpc_timestamp_to_chrono_timestamp)
+        };
+        Self {
+            figi: Figi::from_api(response.figi),
+            instrument_uid: InstrumentUid::from_api(response.instrument_uid),
+            depth: response.depth,
+            bids: levels(&response.bids),
+            asks: levels(&response.asks),
+            last_price: price(&response.last_price),
+            close_price: price(&response.close_price),
+            limit_up: price(&response.limit_up),
+            limit_down: price(&response.limit_down),
+            last_price_time: time(&response.last_price_ts),
+            close_price_time: time(&response.close_price_ts),
+            time: time(&response.orderbook_ts),
+        }
+    }
+}
+
+impl OrderBook {
+    pub fn best_bid(&self) -> Option<&OrderBookLevel> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&OrderBookLevel> {
+        self.asks.first()
+    }
+
+    /// Разница лучших цен продажи и покупки.
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// Спред в базисных пунктах от средней цены.
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        let mid = self.mid_price()?;
+        if mid.is_zero() {
+            return None;
+        }
+        Some(self.spread()? / mid * Decimal::from(10_000))
+    }
+
+    /// Среднее лучших цен покупки и продажи.
+    pub fn mid_price(&self) -> Option<Decimal> {
+        Some((self.best_bid()?.price + self.best_ask()?.price) / Decimal::TWO)
+    }
+
+    /// Средняя цена, взвешенная объёмом на противоположной стороне: ближе к цене той стороны,
+    /// где заявок меньше.
+    pub fn microprice(&self) -> Option<Decimal> {
+        let bid = self.best_bid()?;
+        let ask = self.best_ask()?;
+        let total = bid.quantity + ask.quantity;
+        if total == 0 {
+            return self.mid_price();
+        }
+        Some(
+            (bid.price * Decimal::from(ask.quantity) + ask.price * Decimal::from(bid.quantity))
+                / Decimal::from(total),
+        )
+    }
+
+    /// Сколько лотов стоит в пределах `ticks` шагов цены от лучшей цены стороны.
+    /// `direction` — направление встречной заявки: покупка забирает заявки на продажу.
+    pub fn depth_within(
+        &self,
+        direction: OrderDirection,
+        ticks: u32,
+        min_price_increment: Decimal,
+    ) -> i64 {
+        let levels = self.levels(direction);
+        let best = match levels.first() {
+            Some(level) => level.price,
+            None => return 0,
+        };
+        let limit = min_price_increment * Decimal::from(ticks);
+        levels
+            .iter()
+            .take_while(|level| (level.price - best).abs() <= limit)
+            .map(|level| level.quantity)
+            .sum()
+    }
+
+    /// Дисбаланс первых `levels` уровней: `(bid - ask) / (bid + ask)` по количеству лотов,
+    /// от -1 (только продавцы) до 1 (только покупатели).
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid: i64 = self
+            .bids
+            .iter()
+            .take(levels)
+            .map(|level| level.quantity)
+            .sum();
+        let ask: i64 = self
+            .asks
+            .iter()
+            .take(levels)
+            .map(|level| level.quantity)
+            .sum();
+        if bid + ask == 0 {
+            return None;
+        }
+        Some(Decimal::from(bid - ask) / Decimal::from(bid + ask))
+    }
+
+    /// Средняя цена исполнения рыночной заявки на `lots` лотов по текущему стакану.
+    /// `None`, если в стакане не хватает заявок.
+    pub fn average_fill_price(&self, direction: OrderDirection, lots: i64) -> Option<Decimal> {
+        if lots <= 0 {
+            return None;
+        }
+        let mut remaining = lots;
+        let mut cost = Decimal::ZERO;
+        for level in self.levels(direction) {
+            let filled = remaining.min(level.quantity);
+            cost += level.price * Decimal::from(filled);
+            remaining -= filled;
+            if remaining == 0 {
+                return Some(cost / Decimal::from(lots));
+            }
+        }
+        None
+    }
+
+    fn levels(&self, direction: OrderDirection) -> &[OrderBookLevel] {
+        match direction {
+            OrderDirection::Buy => &self.asks,
+            OrderDirection::Sell => &self.bids,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: i64, quantity: i64) -> OrderBookLevel {
+        OrderBookLevel {
+            price: Decimal::from(price),
+            quantity,
+        }
+    }
+
+    fn book() -> OrderBook {
+        let mut book: OrderBook = api::GetOrderBookResponse {
+            figi: "BBG004730N88".to_owned(),
+            depth: 10,
+            ..Default::default()
+        }
+        .into();
+        book.bids = vec![level(99, 30), level(98, 10), level(95, 100)];
+        book.asks = vec![level(101, 10), level(102, 20), level(110, 5)];
+        book
+    }
+
+    #[test]
+    fn drops_levels_without_price() {
+        let order = |units: Option<i64>| api::Order {
+            price: units.map(|units| api::Quotation { units, nano: 0 }),
+            quantity: 10,
+        };
+        let book: OrderBook = api::GetOrderBookResponse {
+            bids: vec![order(Some(99)), order(None), order(Some(98))],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(book.bids, vec![level(99, 10), level(98, 10)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_ids_from_api_without_validation() {
+        let book: OrderBook = api::GetOrderBookResponse::default().into();
+        let json = serde_json::to_string(&book).unwrap();
+        assert_eq!(serde_json::from_str::<OrderBook>(&json).unwrap(), book);
+    }
+
+    #[test]
+    fn calculates_spread_and_prices() {
+        let book = book();
+        assert_eq!(book.spread(), Some(Decimal::from(2)));
+        assert_eq!(book.mid_price(), Some(Decimal::from(100)));
+        assert_eq!(book.spread_bps(), Some(Decimal::from(200)));
+        // 99 * 10 / 40 + 101 * 30 / 40
+        assert_eq!(book.microprice(), Some(Decimal::new(1005, 1)));
+        assert_eq!(book.imbalance(1), Some(Decimal::new(5, 1)));
+        assert_eq!(book.depth_within(OrderDirection::Sell, 1, Decimal::ONE), 40);
+        assert_eq!(book.depth_within(OrderDirection::Buy, 9, Decimal::ONE), 35);
+    }
+
+    #[test]
+    fn walks_book_for_fill_price() {
+        let book = book();
+        // 10 * 101 + 15 * 102
+        assert_eq!(
+            book.average_fill_price(OrderDirection::Buy, 25),
+            Some(Decimal::new(1016, 1))
+        );
+        assert_eq!(
+            book.average_fill_price(OrderDirection::Sell, 30),
+            Some(Decimal::from(99))
+        );
+        assert_eq!(book.average_fill_price(OrderDirection::Buy, 36), None);
+    }
+}
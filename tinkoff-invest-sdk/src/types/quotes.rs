@@ -0,0 +1,190 @@
+use chrono::{DateTime, Utc};
+use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
+
+use super::{grpc_timestamp_to_chrono_timestamp, Figi, InstrumentUid, SecurityTradingStatus};
+
+/// Цена последней сделки.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LastPrice {
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "Figi::deserialize_unchecked")
+    )]
+    pub figi: Figi,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "InstrumentUid::deserialize_unchecked")
+    )]
+    pub instrument_uid: InstrumentUid,
+    pub price: Decimal,
+    pub time: Option<DateTime<Utc>>,
+}
+
+impl LastPrice {
+    /// `None`, если API не вернул цену: например, сделок по инструменту ещё не было.
+    pub(crate) fn from_api(price: api::LastPrice) -> Option<Self> {
+        Some(Self {
+            price: price.price.as_ref().map(Decimal::from)?,
+            time: price
+                .time
+                .as_ref()
+                .and_then(grpc_timestamp_to_chrono_timestamp),
+            figi: Figi::from_api(price.figi),
+            instrument_uid: InstrumentUid::from_api(price.instrument_uid),
+        })
+    }
+}
+
+/// Цена закрытия торговой сессии.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClosePrice {
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "Figi::deserialize_unchecked")
+    )]
+    pub figi: Figi,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "InstrumentUid::deserialize_unchecked")
+    )]
+    pub instrument_uid: InstrumentUid,
+    pub price: Decimal,
+    pub time: Option<DateTime<Utc>>,
+}
+
+impl ClosePrice {
+    /// `None`, если API не вернул цену.
+    pub(crate) fn from_api(price: api::InstrumentClosePriceResponse) -> Option<Self> {
+        Some(Self {
+            price: price.price.as_ref().map(Decimal::from)?,
+            time: price
+                .time
+                .as_ref()
+                .and_then(grpc_timestamp_to_chrono_timestamp),
+            figi: Figi::from_api(price.figi),
+            instrument_uid: InstrumentUid::from_api(price.instrument_uid),
+        })
+    }
+}
+
+/// Торговый статус инструмента.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TradingStatus {
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "Figi::deserialize_unchecked")
+    )]
+    pub figi: Figi,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "InstrumentUid::deserialize_unchecked")
+    )]
+    pub instrument_uid: InstrumentUid,
+    pub status: SecurityTradingStatus,
+    /// Можно ли выставить лимитную заявку.
+    pub limit_order_available: bool,
+    /// Можно ли выставить рыночную заявку.
+    pub market_order_available: bool,
+    /// Доступна ли торговля через API.
+    pub api_trade_available: bool,
+}
+
+impl From<api::GetTradingStatusResponse> for TradingStatus {
+    fn from(status: api::GetTradingStatusResponse) -> Self {
+        Self {
+            figi: Figi::from_api(status.figi),
+            instrument_uid: InstrumentUid::from_api(status.instrument_uid),
+            status: status.trading_status.into(),
+            limit_order_available: status.limit_order_available_flag,
+            market_order_available: status.market_order_available_flag,
+            api_trade_available: status.api_trade_available_flag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIGI: &str = "BBG004730N88";
+    const UID: &str = "e6123145-9665-43e0-8413-cd61b8aa9b13";
+
+    fn quotation(units: i64, nano: i32) -> Option<api::Quotation> {
+        Some(api::Quotation { units, nano })
+    }
+
+    #[test]
+    fn converts_last_and_close_prices() {
+        let mut last = api::LastPrice {
+            figi: FIGI.to_owned(),
+            instrument_uid: UID.to_owned(),
+            price: quotation(250, 500_000_000),
+            time: Some(tinkoff_invest_grpc::Timestamp {
+                seconds: 1_672_531_200,
+                nanos: 0,
+            }),
+        };
+        let converted = LastPrice::from_api(last.clone()).unwrap();
+        assert_eq!(converted.figi, Figi::from_api(FIGI.to_owned()));
+        assert_eq!(
+            converted.instrument_uid,
+            InstrumentUid::from_api(UID.to_owned())
+        );
+        assert_eq!(converted.price, Decimal::new(2505, 1));
+        assert_eq!(
+            converted.time.unwrap().to_rfc3339(),
+            "2023-01-01T00:00:00+00:00"
+        );
+        last.price = None;
+        assert_eq!(LastPrice::from_api(last), None);
+
+        let mut close = api::InstrumentClosePriceResponse {
+            figi: FIGI.to_owned(),
+            instrument_uid: UID.to_owned(),
+            price: quotation(100, 0),
+            time: None,
+        };
+        let converted = ClosePrice::from_api(close.clone()).unwrap();
+        assert_eq!(converted.price, Decimal::from(100));
+        assert_eq!(converted.time, None);
+        close.price = None;
+        assert_eq!(ClosePrice::from_api(close), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_ids_from_api_without_validation() {
+        let price = LastPrice::from_api(api::LastPrice {
+            figi: String::new(),
+            instrument_uid: "not-a-uuid".to_owned(),
+            price: quotation(1, 0),
+            time: None,
+        })
+        .unwrap();
+        let json = serde_json::to_string(&price).unwrap();
+        assert_eq!(serde_json::from_str::<LastPrice>(&json).unwrap(), price);
+    }
+
+    #[test]
+    fn converts_trading_status() {
+        let status = TradingStatus::from(api::GetTradingStatusResponse {
+            figi: FIGI.to_owned(),
+            instrument_uid: UID.to_owned(),
+            trading_status: api::SecurityTradingStatus::NormalTrading as i32,
+            limit_order_available_flag: true,
+            market_order_available_flag: false,
+            api_trade_available_flag: true,
+        });
+        assert_eq!(status.status, SecurityTradingStatus::NormalTrading);
+        assert!(status.limit_order_available);
+        assert!(!status.market_order_available);
+        assert!(status.api_trade_available);
+        assert_eq!(
+            status.instrument_uid,
+            InstrumentUid::from_api(UID.to_owned())
+        );
+    }
+}
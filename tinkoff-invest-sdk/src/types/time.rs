@@ -0,0 +1,101 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+
+const MOSCOW_OFFSET_SECONDS: i32 = 3 * 3600;
+
+/// Часовой пояс Москвы: UTC+3, без перехода на летнее время.
+#[inline]
+pub fn moscow_offset() -> FixedOffset {
+    FixedOffset::east_opt(MOSCOW_OFFSET_SECONDS).expect("Invalid Moscow offset")
+}
+
+/// Начало даты по Москве.
+pub(crate) fn moscow_day_start(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date
+        .and_hms_opt(0, 0, 0)
+        .expect("Invalid hour/minute/second");
+    moscow_offset()
+        .from_local_datetime(&midnight)
+        .single()
+        .expect("Invalid Moscow time")
+        .with_timezone(&Utc)
+}
+
+/// Перевод времени из API (UTC) в московское — по нему работают биржи.
+pub trait MoscowTime {
+    /// Тот же момент времени по Москве.
+    fn to_moscow(&self) -> DateTime<FixedOffset>;
+
+    /// Дата по Москве.
+    fn moscow_date(&self) -> NaiveDate {
+        self.to_moscow().date_naive()
+    }
+}
+
+impl<Tz: TimeZone> MoscowTime for DateTime<Tz> {
+    #[inline]
+    fn to_moscow(&self) -> DateTime<FixedOffset> {
+        self.with_timezone(&moscow_offset())
+    }
+}
+
+/// Граница интервала в запросе: дата (сутки по UTC) или момент времени.
+///
+/// Методы клиентов обобщены по типу границы, поэтому у интервала `..` его нужно указать явно:
+/// `client.get_bond_coupons::<NaiveDate>(&figi, ..)`.
+pub trait TimestampBound {
+    /// Первый момент, который покрывает граница.
+    fn to_utc(&self) -> DateTime<Utc>;
+
+    /// Первый момент после границы.
+    fn after(&self) -> DateTime<Utc>;
+}
+
+impl TimestampBound for NaiveDate {
+    #[inline]
+    fn to_utc(&self) -> DateTime<Utc> {
+        Utc.from_utc_datetime(
+            &self
+                .and_hms_opt(0, 0, 0)
+                .expect("Invalid hour/minute/second"),
+        )
+    }
+
+    /// Начало следующих суток.
+    #[inline]
+    fn after(&self) -> DateTime<Utc> {
+        self.to_utc() + Duration::days(1)
+    }
+}
+
+impl<Tz: TimeZone> TimestampBound for DateTime<Tz> {
+    #[inline]
+    fn to_utc(&self) -> DateTime<Utc> {
+        self.with_timezone(&Utc)
+    }
+
+    #[inline]
+    fn after(&self) -> DateTime<Utc> {
+        self.to_utc() + Duration::nanoseconds(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_utc_to_moscow() {
+        let utc = Utc.with_ymd_and_hms(2023, 3, 3, 21, 30, 0).unwrap();
+        let moscow = utc.to_moscow();
+        assert_eq!(moscow.naive_local().to_string(), "2023-03-04 00:30:00");
+        assert_eq!(
+            utc.moscow_date(),
+            NaiveDate::from_ymd_opt(2023, 3, 4).unwrap()
+        );
+        assert_eq!(moscow, utc);
+        assert_eq!(
+            (utc - Duration::hours(1)).moscow_date(),
+            NaiveDate::from_ymd_opt(2023, 3, 3).unwrap()
+        );
+    }
+}
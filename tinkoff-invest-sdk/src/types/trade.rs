@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use tinkoff_invest_grpc::{api, decimal::rust_decimal::Decimal};
+
+use super::grpc_timestamp_to_chrono_timestamp;
+use crate::proto_enum;
+
+proto_enum! {
+    pub enum TradeDirection: TradeDirection {
+        /// Направление не определено
+        Unspecified,
+        /// Покупка
+        Buy,
+        /// Продажа
+        Sell,
+    }
+}
+
+/// Обезличенная сделка.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trade {
+    /// Направление инициатора сделки.
+    pub direction: TradeDirection,
+    pub price: Decimal,
+    /// Количество в лотах.
+    pub quantity: i64,
+    pub time: DateTime<Utc>,
+}
+
+impl Trade {
+    /// `None`, если API не вернул время или цену сделки.
+    pub(crate) fn from_api(trade: &api::Trade) -> Option<Self> {
+        Some(Self {
+            direction: trade.direction.into(),
+            price: trade.price.as_ref().map(Decimal::from)?,
+            quantity: trade.quantity,
+            time: trade
+                .time
+                .as_ref()
+                .and_then(grpc_timestamp_to_chrono_timestamp)?,
+        })
+    }
+
+    /// Средняя цена сделок, взвешенная по количеству. `None`, если сделок нет.
+    pub fn vwap(trades: &[Trade]) -> Option<Decimal> {
+        let quantity: i64 = trades.iter().map(|trade| trade.quantity).sum();
+        if quantity == 0 {
+            return None;
+        }
+        let turnover: Decimal = trades
+            .iter()
+            .map(|trade| trade.price * Decimal::from(trade.quantity))
+            .sum();
+        Some(turnover / Decimal::from(quantity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn calculates_vwap() {
+        let trade = |price, quantity| Trade {
+            direction: TradeDirection::Buy,
+            price: Decimal::from(price),
+            quantity,
+            time: Utc.with_ymd_and_hms(2023, 3, 3, 7, 0, 0).unwrap(),
+        };
+        assert_eq!(
+            Trade::vwap(&[trade(100, 1), trade(110, 3)]),
+            Some(Decimal::new(1075, 1))
+        );
+        assert_eq!(Trade::vwap(&[]), None);
+    }
+
+    #[test]
+    fn converts_api_trade() {
+        let mut trade = api::Trade {
+            figi: "BBG004730N88".to_owned(),
+            direction: api::TradeDirection::Sell as i32,
+            price: Some(api::Quotation {
+                units: 250,
+                nano: 500_000_000,
+            }),
+            quantity: 3,
+            time: Some(tinkoff_invest_grpc::Timestamp {
+                seconds: 1_677_826_800,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            Trade::from_api(&trade),
+            Some(Trade {
+                direction: TradeDirection::Sell,
+                price: Decimal::new(2505, 1),
+                quantity: 3,
+                time: Utc.with_ymd_and_hms(2023, 3, 3, 7, 0, 0).unwrap(),
+            })
+        );
+        trade.direction = api::TradeDirection::Buy as i32;
+        assert_eq!(
+            Trade::from_api(&trade).map(|trade| trade.direction),
+            Some(TradeDirection::Buy)
+        );
+        trade.time = None;
+        assert_eq!(Trade::from_api(&trade), None);
+        trade.time = Some(tinkoff_invest_grpc::Timestamp::default());
+        trade.price = None;
+        assert_eq!(Trade::from_api(&trade), None);
+    }
+}
@@ -1,9 +1,11 @@
 use std::ops::RangeInclusive;
 
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, Utc};
 use tinkoff_invest_grpc::api;
 
 use super::grpc_timestamp_to_chrono_timestamp;
+#[cfg(feature = "serde")]
+use crate::serialization::proto_message_serde;
 
 #[repr(transparent)]
 pub struct TradingDay(api::TradingDay);
@@ -18,7 +20,7 @@ impl TradingDay {
     #[inline(always)]
     pub fn date(&self) -> Option<NaiveDate> {
         let date = self.0.date.as_ref()?;
-        Some(grpc_timestamp_to_chrono_timestamp(date)?.date())
+        Some(grpc_timestamp_to_chrono_timestamp(date)?.date_naive())
     }
 
     #[inline(always)]
@@ -27,7 +29,7 @@ impl TradingDay {
     }
 
     #[inline(always)]
-    pub fn trading_time(&self) -> Option<RangeInclusive<NaiveDateTime>> {
+    pub fn trading_time(&self) -> Option<RangeInclusive<DateTime<Utc>>> {
         let start = self.0.start_time.as_ref()?;
         let end = self.0.end_time.as_ref()?;
         let start = grpc_timestamp_to_chrono_timestamp(start)?;
@@ -36,7 +38,7 @@ impl TradingDay {
     }
 
     #[inline(always)]
-    pub fn opening_auction_start_time(&self) -> Option<NaiveDateTime> {
+    pub fn opening_auction_start_time(&self) -> Option<DateTime<Utc>> {
         self.0
             .opening_auction_start_time
             .as_ref()
@@ -44,7 +46,7 @@ impl TradingDay {
     }
 
     #[inline(always)]
-    pub fn closing_auction_end_time(&self) -> Option<NaiveDateTime> {
+    pub fn closing_auction_end_time(&self) -> Option<DateTime<Utc>> {
         self.0
             .closing_auction_end_time
             .as_ref()
@@ -52,7 +54,7 @@ impl TradingDay {
     }
 
     #[inline(always)]
-    pub fn evening_opening_auction_start_time(&self) -> Option<NaiveDateTime> {
+    pub fn evening_opening_auction_start_time(&self) -> Option<DateTime<Utc>> {
         self.0
             .evening_opening_auction_start_time
             .as_ref()
@@ -60,21 +62,21 @@ impl TradingDay {
     }
 
     #[inline(always)]
-    pub fn evening_trading_time(&self) -> Option<RangeInclusive<NaiveDateTime>> {
+    pub fn evening_trading_time(&self) -> Option<RangeInclusive<DateTime<Utc>>> {
         let start = grpc_timestamp_to_chrono_timestamp(self.0.evening_start_time.as_ref()?)?;
         let end = grpc_timestamp_to_chrono_timestamp(self.0.evening_end_time.as_ref()?)?;
         Some(start..=end)
     }
 
     #[inline(always)]
-    pub fn clearing_time(&self) -> Option<RangeInclusive<NaiveDateTime>> {
+    pub fn clearing_time(&self) -> Option<RangeInclusive<DateTime<Utc>>> {
         let start = grpc_timestamp_to_chrono_timestamp(self.0.clearing_start_time.as_ref()?)?;
         let end = grpc_timestamp_to_chrono_timestamp(self.0.clearing_end_time.as_ref()?)?;
         Some(start..=end)
     }
 
     #[inline(always)]
-    pub fn premarket_time(&self) -> Option<RangeInclusive<NaiveDateTime>> {
+    pub fn premarket_time(&self) -> Option<RangeInclusive<DateTime<Utc>>> {
         let start = grpc_timestamp_to_chrono_timestamp(self.0.premarket_start_time.as_ref()?)?;
         let end = grpc_timestamp_to_chrono_timestamp(self.0.premarket_end_time.as_ref()?)?;
         Some(start..=end)
@@ -102,3 +104,30 @@ impl TradingSchedule {
         unsafe { std::mem::transmute(self.0.days.as_slice()) }
     }
 }
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    TradingDay.0: api::TradingDay {
+        date: Option<NaiveDate>,
+        is_trading_day: bool,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        opening_auction_start_time: Option<DateTime<Utc>>,
+        closing_auction_end_time: Option<DateTime<Utc>>,
+        evening_opening_auction_start_time: Option<DateTime<Utc>>,
+        evening_start_time: Option<DateTime<Utc>>,
+        evening_end_time: Option<DateTime<Utc>>,
+        clearing_start_time: Option<DateTime<Utc>>,
+        clearing_end_time: Option<DateTime<Utc>>,
+        premarket_start_time: Option<DateTime<Utc>>,
+        premarket_end_time: Option<DateTime<Utc>>,
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    TradingSchedule.0: api::TradingSchedule {
+        exchange: String,
+        days: Vec<TradingDay>,
+    }
+}
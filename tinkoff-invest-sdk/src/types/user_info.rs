@@ -1,5 +1,8 @@
 use tinkoff_invest_grpc::api;
 
+#[cfg(feature = "serde")]
+use crate::serialization::proto_message_serde;
+
 #[repr(transparent)]
 #[derive(Debug, Clone)]
 pub struct UserInfo(api::GetInfoResponse);
@@ -106,4 +109,38 @@ impl UserTariff {
         // note: Возможно, можно как-то это сделать без unsafe
         unsafe { ::std::mem::transmute(borrowed) }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    UserInfo.0: api::GetInfoResponse {
+        prem_status: bool,
+        qual_status: bool,
+        qualified_for_work_with: Vec<String>,
+        tariff: String,
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    UnaryLimit.0: api::UnaryLimit {
+        limit_per_minute: i32,
+        methods: Vec<String>,
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    StreamLimit.0: api::StreamLimit {
+        limit: i32,
+        streams: Vec<String>,
+    }
+}
+
+#[cfg(feature = "serde")]
+proto_message_serde! {
+    UserTariff.0: api::GetUserTariffResponse {
+        unary_limits: Vec<UnaryLimit>,
+        stream_limits: Vec<StreamLimit>,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::cache::{Cache, CachedUsersClient};
 use crate::{service, types};
 use tinkoff_invest_grpc::api::users_service_client::UsersServiceClient;
 
@@ -10,11 +11,15 @@ use tinkoff_invest_grpc::*;
 service!(UsersClient, UsersServiceClient<Inner>);
 
 impl UsersClient {
+    /// Добавить кеширование справочных методов.
+    pub fn with_cache(self, cache: Cache) -> CachedUsersClient {
+        CachedUsersClient::new(self, cache)
+    }
+
     /// Получить все счета пользователя
     pub async fn get_accounts(&mut self) -> crate::Result<Vec<types::Account>> {
         let request = api::GetAccountsRequest {};
-        let response = self.internal.get_accounts(request).await?;
-        let data = response.into_inner();
+        let data = self.get_accounts_raw(request).await?;
         Ok(data
             .accounts
             .into_iter()
@@ -22,26 +27,45 @@ impl UsersClient {
             .collect())
     }
 
+    pub(crate) async fn get_accounts_raw(
+        &mut self,
+        request: api::GetAccountsRequest,
+    ) -> crate::Result<api::GetAccountsResponse> {
+        Ok(self.internal.get_accounts(request).await?.into_inner())
+    }
+
     pub async fn get_user_tariff(&mut self) -> crate::Result<types::UserTariff> {
         let request = api::GetUserTariffRequest {};
-        let response = self.internal.get_user_tariff(request).await?;
-        let tariff = response.into_inner();
+        let tariff = self.get_user_tariff_raw(request).await?;
         Ok(tariff.into())
     }
 
+    pub(crate) async fn get_user_tariff_raw(
+        &mut self,
+        request: api::GetUserTariffRequest,
+    ) -> crate::Result<api::GetUserTariffResponse> {
+        Ok(self.internal.get_user_tariff(request).await?.into_inner())
+    }
+
     pub async fn get_info(&mut self) -> crate::Result<types::UserInfo> {
         let request = api::GetInfoRequest {};
-        let response = self.internal.get_info(request).await?;
-        let data = response.into_inner();
+        let data = self.get_info_raw(request).await?;
         Ok(types::UserInfo::from(data))
     }
 
+    pub(crate) async fn get_info_raw(
+        &mut self,
+        request: api::GetInfoRequest,
+    ) -> crate::Result<api::GetInfoResponse> {
+        Ok(self.internal.get_info(request).await?.into_inner())
+    }
+
     pub async fn get_margin_attributes(
         &mut self,
-        account_id: impl Into<String>,
+        account_id: &types::AccountId,
     ) -> crate::Result<types::MarginAttributes> {
         let request = api::GetMarginAttributesRequest {
-            account_id: account_id.into(),
+            account_id: account_id.to_string(),
         };
         let response = self.internal.get_margin_attributes(request).await?;
         let data = response.into_inner();